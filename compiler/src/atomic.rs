@@ -3,11 +3,13 @@
 //! Configured via environment:
 //!   ESC_ATOMIC_URL  — server base URL (e.g. http://192.168.1.128:9884)
 //!   ESC_ATOMIC_KEY  — agent private key (base64 Ed25519)
+//! falling back to `atomic_url`/`atomic_key` of the active config profile.
 //!
 //! Uses two query mechanisms:
 //!   /search — full-text search (tantivy, indexes name+description)
 //!   /query  — structured property+value filtering (sled indexes, server-side)
 
+use crate::log;
 use base64::prelude::*;
 use ed25519_dalek::{Signer, SigningKey};
 use serde_json::Value;
@@ -44,11 +46,16 @@ pub struct AtomicClient {
 }
 
 impl AtomicClient {
-    /// Create client from ESC_ATOMIC_URL + ESC_ATOMIC_KEY env vars.
-    /// Returns None if either is missing.
+    /// Create client from ESC_ATOMIC_URL + ESC_ATOMIC_KEY env vars, or the
+    /// active config profile. Returns None if either is missing.
     pub fn from_env() -> Option<Self> {
-        let server_url = std::env::var("ESC_ATOMIC_URL").ok()?;
-        let private_key_b64 = std::env::var("ESC_ATOMIC_KEY").ok()?;
+        let profile = &crate::config::get().active;
+        let server_url = std::env::var("ESC_ATOMIC_URL")
+            .ok()
+            .or_else(|| profile.atomic_url.clone())?;
+        let private_key_b64 = std::env::var("ESC_ATOMIC_KEY")
+            .ok()
+            .or_else(|| profile.atomic_key.clone())?;
 
         let key_bytes = BASE64_STANDARD.decode(private_key_b64.as_bytes()).ok()?;
         let key_array: [u8; 32] = key_bytes.try_into().ok()?;
@@ -87,7 +94,7 @@ impl AtomicClient {
     pub fn get(&self, url: &str) -> Result<Value, String> {
         let headers = self.auth_headers(url);
         let (code, body) = curl_get(url, &headers)?;
-        if code >= 200 && code < 300 {
+        if (200..300).contains(&code) {
             serde_json::from_str(&body).map_err(|e| format!("json: {e}"))
         } else {
             Err(format!("HTTP {code}: {body}"))
@@ -104,6 +111,13 @@ impl AtomicClient {
     /// Full-text search via /search endpoint (tantivy).
     /// Returns resolved resources matching the note URL prefix.
     pub fn search(&self, query: &str, limit: usize) -> Result<Vec<Value>, String> {
+        self.search_capped(query, limit)
+            .map(|(resolved, _)| resolved)
+    }
+
+    /// Like `search`, also reporting whether the server returned a full
+    /// `limit` of hits, i.e. the result may be cut off.
+    pub fn search_capped(&self, query: &str, limit: usize) -> Result<(Vec<Value>, bool), String> {
         let url = format!(
             "{}/search?q={}&limit={}",
             self.server_url,
@@ -117,19 +131,19 @@ impl AtomicClient {
             .and_then(|v| v.as_array())
             .cloned()
             .unwrap_or_default();
+        let capped = members.len() >= limit;
 
         let prefix = format!("{}/esc/note/", self.server_url);
         let mut resolved = Vec::new();
         for member in members {
-            if let Some(url) = member.as_str() {
-                if url.starts_with(&prefix) {
-                    if let Ok(r) = self.get(url) {
-                        resolved.push(r);
-                    }
-                }
+            if let Some(url) = member.as_str()
+                && url.starts_with(&prefix)
+                && let Ok(r) = self.get(url)
+            {
+                resolved.push(r);
             }
         }
-        Ok(resolved)
+        Ok((resolved, capped))
     }
 
     // --- Writes ---
@@ -176,7 +190,7 @@ impl AtomicClient {
             serde_json::to_string(&Value::Object(fields)).map_err(|e| format!("json: {e}"))?;
 
         let (code, resp) = curl_post(&format!("{}/commit", self.server_url), &body, &[])?;
-        if code >= 200 && code < 300 {
+        if (200..300).contains(&code) {
             Ok(())
         } else {
             Err(format!("commit HTTP {code}: {resp}"))
@@ -216,7 +230,7 @@ impl AtomicClient {
             serde_json::to_string(&Value::Object(fields)).map_err(|e| format!("json: {e}"))?;
 
         let (code, resp) = curl_post(&format!("{}/commit", self.server_url), &body, &[])?;
-        if code >= 200 && code < 300 {
+        if (200..300).contains(&code) {
             Ok(())
         } else {
             Err(format!("destroy HTTP {code}: {resp}"))
@@ -291,6 +305,29 @@ impl AtomicClient {
             )?;
         }
 
+        // Added after the note schema; servers set up earlier only lack these
+        let later_props: &[(&str, &str)] = &[
+            (
+                "namespace",
+                "Memory namespace (omitted for the default namespace)",
+            ),
+            ("updated", "Last modification timestamp (ISO 8601)"),
+        ];
+        for (name, desc) in later_props {
+            if !self.exists(&self.prop_url(name)) {
+                self.create(
+                    &self.prop_url(name),
+                    &serde_json::json!({
+                        PROP_IS_A: [CLASS_PROPERTY],
+                        PROP_SHORTNAME: format!("esc-{name}"),
+                        PROP_DESCRIPTION: *desc,
+                        PROP_DATATYPE: DT_STRING,
+                        PROP_PARENT: s,
+                    }),
+                )?;
+            }
+        }
+
         Ok(())
     }
 }
@@ -366,7 +403,11 @@ fn curl_get(url: &str, headers: &[(String, String)]) -> Result<(u16, String), St
     }
     cmd.arg(url);
     let output = cmd.output().map_err(|e| format!("curl: {e}"))?;
-    parse_curl_output(&output.stdout)
+    let result = parse_curl_output(&output.stdout);
+    if let Ok((code, _)) = &result {
+        log::debug!("GET {url} -> {code}");
+    }
+    result
 }
 
 fn curl_post(url: &str, body: &str, headers: &[(String, String)]) -> Result<(u16, String), String> {
@@ -390,7 +431,11 @@ fn curl_post(url: &str, body: &str, headers: &[(String, String)]) -> Result<(u16
     cmd.arg("-d").arg(body);
     cmd.arg(url);
     let output = cmd.output().map_err(|e| format!("curl: {e}"))?;
-    parse_curl_output(&output.stdout)
+    let result = parse_curl_output(&output.stdout);
+    if let Ok((code, _)) = &result {
+        log::debug!("POST {url} -> {code}");
+    }
+    result
 }
 
 fn parse_curl_output(stdout: &[u8]) -> Result<(u16, String), String> {
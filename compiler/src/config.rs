@@ -0,0 +1,186 @@
+//! User configuration — `~/.esc/config.toml` — and the esc home directory.
+//!
+//! The home directory (`~/.esc` by default) can be moved with `--home` or
+//! `ESC_HOME`; memory, the atomic outbox and this config file all live in it.
+//!
+//! Every section and key is optional; missing values fall back to defaults.
+//!
+//! ```toml
+//! profile = "homelab"   # used when --profile / ESC_PROFILE is not given
+//!
+//! [recall]
+//! summary = 3       # query word found in summary
+//! detail = 2        # ... in detail
+//! kind = 2          # word equals the note kind
+//! context = 2       # ... in context
+//! tag_exact = 3     # word equals a tag
+//! tag_partial = 1   # word is part of a tag
+//! recency = 2       # max bonus for a note created just now
+//! recency_half_life_days = 30
+//!
+//! [profiles.homelab]
+//! atomic_url = "http://192.168.1.128:9884"
+//! atomic_key = "<base64 Ed25519 private key>"
+//! namespace = "homelab"
+//! recall = { recency = 5 }   # replaces [recall] while the profile is active
+//! ```
+//!
+//! Flags and environment variables win over the active profile.
+
+use crate::log;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+static HOME: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub recall: RecallWeights,
+    /// Default profile name
+    pub profile: Option<String>,
+    pub profiles: HashMap<String, Profile>,
+    /// Resolved profile settings (empty when no profile is active)
+    #[serde(skip)]
+    pub active: Profile,
+}
+
+/// Named bundle of settings selected with `--profile`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub atomic_url: Option<String>,
+    pub atomic_key: Option<String>,
+    pub namespace: Option<String>,
+    pub recall: Option<RecallWeights>,
+}
+
+/// Score weights for `memory search`. Keyword weights are added per query
+/// word; the recency bonus halves every `recency_half_life_days`.
+/// Unknown keys are an error, so a top-level key written below `[recall]`
+/// (and so parsed into it) gets reported instead of silently dropped.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecallWeights {
+    pub summary: usize,
+    pub detail: usize,
+    pub kind: usize,
+    pub context: usize,
+    pub tag_exact: usize,
+    pub tag_partial: usize,
+    pub recency: usize,
+    pub recency_half_life_days: f64,
+}
+
+impl Default for RecallWeights {
+    fn default() -> Self {
+        RecallWeights {
+            summary: 3,
+            detail: 2,
+            kind: 2,
+            context: 2,
+            tag_exact: 3,
+            tag_partial: 1,
+            recency: 2,
+            recency_half_life_days: 30.0,
+        }
+    }
+}
+
+/// Override the esc home directory for this process. First call wins.
+pub fn set_home(dir: PathBuf) {
+    let _ = HOME.set(dir);
+}
+
+/// esc state directory: `--home`/`ESC_HOME` if given, else `~/.esc`.
+/// The user's home is `HOME` on Unix and `USERPROFILE` on Windows.
+pub fn esc_home() -> &'static PathBuf {
+    HOME.get_or_init(|| {
+        let home = std::env::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        home.join(".esc")
+    })
+}
+
+fn config_path() -> PathBuf {
+    esc_home().join("config.toml")
+}
+
+/// Load the config file and activate a profile (the given one, else the
+/// file's `profile` key). Called once from main; fails on unknown profiles.
+pub fn init(profile: Option<&str>) -> Result<(), String> {
+    let config = load(profile)?;
+    let _ = CONFIG.set(config);
+    Ok(())
+}
+
+/// Loaded config. A missing file means defaults; a malformed one is
+/// reported on stderr and ignored.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        load(None).unwrap_or_else(|e| {
+            log::warn!("{e}");
+            Config::default()
+        })
+    })
+}
+
+fn load(profile: Option<&str>) -> Result<Config, String> {
+    let path = config_path();
+    let mut config: Config = match std::fs::read_to_string(&path) {
+        Ok(s) => {
+            log::debug!("config {}", path.display());
+            toml::from_str(&s).unwrap_or_else(|e| {
+                log::warn!("{}: {e}", path.display());
+                Config::default()
+            })
+        }
+        Err(_) => Config::default(),
+    };
+
+    let name = profile
+        .map(str::to_string)
+        .or_else(|| config.profile.clone());
+    if let Some(name) = name {
+        let active = config.profiles.get(&name).cloned().ok_or_else(|| {
+            let mut known: Vec<&str> = config.profiles.keys().map(|k| k.as_str()).collect();
+            known.sort();
+            format!(
+                "unknown profile: {name} (known: {})",
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            )
+        })?;
+        if let Some(recall) = &active.recall {
+            config.recall = recall.clone();
+        }
+        config.active = active;
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+
+    #[test]
+    fn documented_example_sets_default_profile() {
+        let config: Config = toml::from_str(
+            "profile = \"homelab\"\n\n[recall]\nrecency = 2\n\n[profiles.homelab]\nnamespace = \"homelab\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.profile.as_deref(), Some("homelab"));
+        assert!(config.profiles.contains_key("homelab"));
+    }
+
+    #[test]
+    fn profile_key_under_recall_is_rejected() {
+        let parsed = toml::from_str::<Config>("[recall]\nrecency = 2\nprofile = \"homelab\"\n");
+        assert!(parsed.is_err());
+    }
+}
@@ -0,0 +1,98 @@
+//! `esc doctor` — preflight for fresh hosts.
+//!
+//! Each check reports `ok`, `fail` or `skip` with a one-line detail; the
+//! whole report goes to stdout as JSON. Atomic-server is skipped when no
+//! credentials are configured, since local memory works without it.
+
+use crate::atomic::AtomicClient;
+use crate::config;
+use serde_json::{Value, json};
+use std::process::Command;
+
+/// Warn below this much free space in the esc home (MiB).
+const MIN_FREE_MB: u64 = 64;
+
+/// Run all checks. Returns the report and whether every check passed.
+pub fn run() -> (Value, bool) {
+    let checks = vec![
+        check("curl", curl(), ""),
+        check("atomic", atomic(), "no credentials configured"),
+        check("home", home(), ""),
+        check("disk", disk(), "no df on this platform"),
+    ];
+    let ok = checks.iter().all(|c| c["status"] != "fail");
+    (
+        json!({
+            "ok": ok,
+            "home": config::esc_home().display().to_string(),
+            "checks": checks,
+        }),
+        ok,
+    )
+}
+
+/// `Ok(Some)` passed, `Ok(None)` skipped, `Err` failed.
+type Outcome = Result<Option<String>, String>;
+
+fn check(name: &str, outcome: Outcome, skip_reason: &str) -> Value {
+    let (status, detail) = match outcome {
+        Ok(Some(detail)) => ("ok", detail),
+        Ok(None) => ("skip", skip_reason.to_string()),
+        Err(detail) => ("fail", detail),
+    };
+    json!({ "name": name, "status": status, "detail": detail })
+}
+
+fn curl() -> Outcome {
+    let output = Command::new("curl")
+        .arg("--version")
+        .output()
+        .map_err(|e| format!("curl not runnable: {e}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(Some(stdout.lines().next().unwrap_or("curl").to_string()))
+}
+
+fn atomic() -> Outcome {
+    let Some(client) = AtomicClient::from_env() else {
+        return Ok(None);
+    };
+    client
+        .get(&client.server_url)
+        .map(|_| Some(format!("reachable at {}", client.server_url)))
+        .map_err(|e| format!("{}: {e}", client.server_url))
+}
+
+fn home() -> Outcome {
+    let dir = config::esc_home();
+    std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    let probe = dir.join(".doctor");
+    std::fs::write(&probe, b"ok").map_err(|e| format!("{}: {e}", dir.display()))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(Some(format!("{} is writable", dir.display())))
+}
+
+/// Free space via POSIX `df -Pk`; no portable std API for this.
+fn disk() -> Outcome {
+    if cfg!(windows) {
+        return Ok(None);
+    }
+    let dir = config::esc_home();
+    let output = Command::new("df")
+        .arg("-Pk")
+        .arg(dir)
+        .output()
+        .map_err(|e| format!("df not runnable: {e}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let avail_kb: u64 = stdout
+        .lines()
+        .nth(1)
+        .and_then(|line| line.split_whitespace().nth(3))
+        .and_then(|n| n.parse().ok())
+        .ok_or_else(|| format!("cannot parse df output for {}", dir.display()))?;
+    let mb = avail_kb / 1024;
+    if mb < MIN_FREE_MB {
+        Err(format!("{mb} MiB free (< {MIN_FREE_MB} MiB)"))
+    } else {
+        Ok(Some(format!("{mb} MiB free")))
+    }
+}
@@ -0,0 +1,172 @@
+//! Leveled diagnostics on stderr, with an optional JSON-lines sink.
+//!
+//! Filtered by `ESC_LOG`: a default level optionally followed by per-module
+//! overrides, e.g. `ESC_LOG=warn` or `ESC_LOG=info,atomic=debug`.
+//! Levels: error, warn, info (default), debug.
+//!
+//! `--log-json <file>` (or `ESC_LOG_JSON`) appends every event that passes
+//! the filter as `{"ts_ms","level","target","msg"}` to the file.
+//! Results still go to stdout; only diagnostics and status lines come here.
+//! `--quiet` (alias `--machine`) silences stderr entirely.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    fn parse(s: &str) -> Option<Level> {
+        match s.trim().to_lowercase().as_str() {
+            "error" => Some(Level::Error),
+            "warn" | "warning" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+        }
+    }
+}
+
+struct Logger {
+    default: Level,
+    overrides: Vec<(String, Level)>,
+    json: Option<Mutex<std::fs::File>>,
+    /// `--quiet`: nothing on stderr; the JSON sink still records
+    quiet: bool,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Set up filtering from `ESC_LOG` and the optional JSON sink. First call wins;
+/// logging before `init` uses the defaults without a sink.
+pub fn init(json_path: Option<PathBuf>, quiet: bool) {
+    let json = json_path.and_then(|path| {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        match std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+        {
+            Ok(f) => Some(Mutex::new(f)),
+            Err(e) => {
+                if !quiet {
+                    eprintln!("warning: {}: {e}", path.display());
+                }
+                None
+            }
+        }
+    });
+    let _ = LOGGER.set(build(std::env::var("ESC_LOG").ok().as_deref(), json, quiet));
+}
+
+fn build(filter: Option<&str>, json: Option<Mutex<std::fs::File>>, quiet: bool) -> Logger {
+    let mut default = Level::Info;
+    let mut overrides = Vec::new();
+    for part in filter
+        .unwrap_or("")
+        .split(',')
+        .filter(|p| !p.trim().is_empty())
+    {
+        match part.split_once('=') {
+            Some((target, level)) => {
+                if let Some(level) = Level::parse(level) {
+                    overrides.push((target.trim().to_string(), level));
+                }
+            }
+            None => {
+                if let Some(level) = Level::parse(part) {
+                    default = level;
+                }
+            }
+        }
+    }
+    Logger {
+        default,
+        overrides,
+        json,
+        quiet,
+    }
+}
+
+/// Short target name from a module path: `esc::memory` → `memory`, `esc` → `main`.
+fn target(module: &str) -> &str {
+    match module.strip_prefix("esc::") {
+        Some(rest) => rest,
+        None => "main",
+    }
+}
+
+#[doc(hidden)]
+pub fn emit(level: Level, module: &str, msg: String) {
+    let logger =
+        LOGGER.get_or_init(|| build(std::env::var("ESC_LOG").ok().as_deref(), None, false));
+    let target = target(module);
+    let max = logger
+        .overrides
+        .iter()
+        .find(|(t, _)| t == target)
+        .map(|(_, l)| *l)
+        .unwrap_or(logger.default);
+    if level > max {
+        return;
+    }
+
+    match level {
+        _ if logger.quiet => {}
+        Level::Error => eprintln!("error: {msg}"),
+        Level::Warn => eprintln!("warning: {msg}"),
+        Level::Info => eprintln!("{msg}"),
+        Level::Debug => eprintln!("debug[{target}]: {msg}"),
+    }
+
+    if let Some(json) = &logger.json {
+        let ts_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let event = serde_json::json!({
+            "ts_ms": ts_ms,
+            "level": level.as_str(),
+            "target": target,
+            "msg": msg,
+        });
+        if let Ok(mut f) = json.lock() {
+            let _ = writeln!(f, "{event}");
+        }
+    }
+}
+
+macro_rules! error {
+    ($($arg:tt)*) => { $crate::log::emit($crate::log::Level::Error, module_path!(), format!($($arg)*)) };
+}
+
+macro_rules! warning {
+    ($($arg:tt)*) => { $crate::log::emit($crate::log::Level::Warn, module_path!(), format!($($arg)*)) };
+}
+
+macro_rules! info {
+    ($($arg:tt)*) => { $crate::log::emit($crate::log::Level::Info, module_path!(), format!($($arg)*)) };
+}
+
+macro_rules! debug {
+    ($($arg:tt)*) => { $crate::log::emit($crate::log::Level::Debug, module_path!(), format!($($arg)*)) };
+}
+
+pub(crate) use {debug, error, info, warning as warn};
@@ -1,19 +1,47 @@
 mod atomic;
+mod config;
+mod doctor;
+mod log;
 mod memory;
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use std::io::Write;
 
 #[derive(Parser)]
 #[command(name = "esc", about = "esc — persistent memory graph")]
 struct Cli {
+    /// esc state directory (default: ~/.esc)
+    #[arg(long, global = true, env = "ESC_HOME")]
+    home: Option<std::path::PathBuf>,
+    /// Config profile from config.toml
+    #[arg(long, global = true, env = "ESC_PROFILE")]
+    profile: Option<String>,
+    /// Append structured log events (JSON lines) to this file; filter with ESC_LOG
+    #[arg(long, global = true, env = "ESC_LOG_JSON")]
+    log_json: Option<std::path::PathBuf>,
+    /// Machine mode: no stderr output, results on stdout and the exit code only
+    #[arg(short, long, visible_alias = "machine", global = true)]
+    quiet: bool,
     #[command(subcommand)]
     cmd: Cmd,
 }
 
 #[derive(Subcommand)]
 enum Cmd {
+    /// Print a shell completion script
+    Completions {
+        /// Target shell
+        shell: clap_complete::Shell,
+    },
+    /// Dump the full command tree with flags as JSON
+    CliSchema,
+    /// Preflight checks (curl, atomic-server, esc home, disk) as JSON
+    Doctor,
     /// Query and manage the memory graph
     Memory {
+        /// Memory namespace to operate on (default: profile's, else "default")
+        #[arg(long, global = true, env = "ESC_MEMORY_NS")]
+        namespace: Option<String>,
         #[command(subcommand)]
         action: MemoryAction,
     },
@@ -28,6 +56,17 @@ enum MemoryAction {
         /// Max results to return
         #[arg(short = 'n', long, default_value = "20")]
         limit: usize,
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[command(flatten)]
+        page: PageArgs,
+        /// Print one compact JSON result per line as results come in
+        #[arg(long)]
+        stream: bool,
+        /// Add a per-result score breakdown (matched words and fields,
+        /// recency bonus, tag expansion source)
+        #[arg(long)]
+        explain: bool,
     },
     /// Show full details of a note by hash prefix
     Show {
@@ -39,6 +78,34 @@ enum MemoryAction {
         /// Max entries to show
         #[arg(short = 'n', long, default_value = "10")]
         limit: usize,
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Notes bucketed by day with counts and highlights, newest first
+    Timeline {
+        /// Start of the window: 7d, 12h, 2w or YYYY-MM-DD
+        #[arg(long, default_value = "7d")]
+        since: String,
+        /// Print a human-readable table instead of JSON
+        #[arg(long)]
+        table: bool,
+    },
+    /// Export notes and links as Graphviz DOT or GraphML
+    Graph {
+        /// Output file (stdout if omitted)
+        #[arg(short = 'o', long)]
+        output: Option<std::path::PathBuf>,
+        /// Format: dot, graphml (default: from output extension, else dot)
+        #[arg(long)]
+        format: Option<String>,
+        /// Only include notes with this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Only include notes with this context
+        #[arg(long)]
+        context: Option<String>,
     },
     /// Store a contextual note (discovery, decision, pattern, issue)
     Note {
@@ -56,6 +123,9 @@ enum MemoryAction {
         /// Comma-separated tags
         #[arg(long, default_value = "")]
         tags: String,
+        /// Link this note to another note (hash prefix, repeatable)
+        #[arg(long)]
+        about: Vec<String>,
     },
     /// List contextual notes with optional filters
     Notes {
@@ -68,6 +138,8 @@ enum MemoryAction {
         /// Max entries to show
         #[arg(short = 'n', long, default_value = "20")]
         limit: usize,
+        #[command(flatten)]
+        page: PageArgs,
     },
     /// Mark a note resolved or superseded
     Resolve {
@@ -77,36 +149,366 @@ enum MemoryAction {
         #[arg(long, default_value = "resolved")]
         status: String,
     },
-    /// Sync all notes to atomic-server (requires ESC_ATOMIC_URL + ESC_ATOMIC_KEY)
+    /// Replay queued writes, then sync all notes to atomic-server (requires ESC_ATOMIC_URL + ESC_ATOMIC_KEY or a profile)
     Sync,
+    /// Two-way merge with atomic-server: newer side wins, ties reported as conflicts
+    Reconcile {
+        /// Report what would change without writing either side
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// Delete notes from atomic-server that don't exist locally
     Purge,
-    /// Create esc schema on atomic-server (requires ESC_ATOMIC_URL + ESC_ATOMIC_KEY)
+    /// Create or upgrade the esc schema on atomic-server; re-run after upgrading esc
+    /// (requires ESC_ATOMIC_URL + ESC_ATOMIC_KEY)
     Setup,
+    /// Inspect and merge memory namespaces
+    Ns {
+        #[command(subcommand)]
+        action: NsAction,
+    },
+}
+
+/// Note filters shared by search and log.
+#[derive(clap::Args)]
+struct FilterArgs {
+    /// Only notes created at or after: 7d, 12h, 2w or YYYY-MM-DD
+    #[arg(long)]
+    since: Option<String>,
+    /// Only notes created up to and including: 7d, 12h, 2w or YYYY-MM-DD
+    #[arg(long)]
+    until: Option<String>,
+    /// Only notes with this status (active, resolved, superseded)
+    #[arg(long)]
+    status: Option<String>,
+    /// Only notes carrying this tag (repeatable; all must match)
+    #[arg(long = "tag")]
+    tags: Vec<String>,
+}
+
+impl FilterArgs {
+    /// Validated filter; any error here is a validation exit.
+    fn resolve(self) -> memory::NoteFilter {
+        self.parse().unwrap_or_else(|e| fail(EXIT_VALIDATION, e))
+    }
+
+    fn parse(self) -> Result<memory::NoteFilter, String> {
+        if let Some(status) = &self.status
+            && !STATUSES.contains(&status.as_str())
+        {
+            return Err(format!(
+                "unknown status: {status} (valid: {})",
+                STATUSES.join(", ")
+            ));
+        }
+        let bound = |spec: Option<String>| spec.map(|s| memory::parse_time_bound(&s)).transpose();
+        Ok(memory::NoteFilter {
+            since: bound(self.since)?,
+            until: bound(self.until)?,
+            status: self.status,
+            tags: self.tags,
+        })
+    }
+}
+
+/// Paging flags shared by search, log and notes.
+#[derive(clap::Args)]
+struct PageArgs {
+    /// Skip this many results
+    #[arg(long, default_value = "0", conflicts_with = "cursor")]
+    offset: usize,
+    /// Continue after the result with this hash (the last one you got)
+    #[arg(long)]
+    cursor: Option<String>,
+}
+
+impl PageArgs {
+    fn with_limit(self, limit: usize) -> memory::Page {
+        memory::Page {
+            limit,
+            offset: self.offset,
+            cursor: self.cursor,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum NsAction {
+    /// List namespaces with note counts
+    List,
+    /// Move all notes of a namespace into another one
+    Merge {
+        /// Source namespace (emptied by the merge)
+        from: String,
+        /// Target namespace (defaults to the active namespace)
+        #[arg(long)]
+        into: Option<String>,
+    },
+}
+
+// Exit codes, stable for orchestration scripts. Anything else is 1;
+// clap's own usage errors already exit 2.
+const EXIT_VALIDATION: i32 = 2;
+const EXIT_IO: i32 = 4;
+const EXIT_NOT_FOUND: i32 = 6;
+
+const STATUSES: [&str; 3] = ["resolved", "superseded", "active"];
+
+fn fail(code: i32, msg: impl std::fmt::Display) -> ! {
+    log::error!("{msg}");
+    std::process::exit(code);
+}
+
+/// Exit for a hash prefix that didn't match exactly one note: unknown
+/// hashes are not-found, empty or ambiguous prefixes are bad input.
+fn fail_lookup(e: memory::HashLookupError) -> ! {
+    let code = match e {
+        memory::HashLookupError::NotFound(_) => EXIT_NOT_FOUND,
+        _ => EXIT_VALIDATION,
+    };
+    fail(code, e)
+}
+
+/// Atomic-server client, or a validation exit when credentials are missing.
+fn require_atomic() -> atomic::AtomicClient {
+    atomic::AtomicClient::from_env().unwrap_or_else(|| {
+        fail(
+            EXIT_VALIDATION,
+            "set ESC_ATOMIC_URL and ESC_ATOMIC_KEY (or atomic_url/atomic_key in a config profile)",
+        )
+    })
 }
 
 fn main() {
     let cli = Cli::parse();
+    log::init(cli.log_json, cli.quiet);
+    if let Some(home) = cli.home {
+        config::set_home(home);
+    }
+    if let Err(e) = config::init(cli.profile.as_deref()) {
+        fail(EXIT_VALIDATION, e);
+    }
 
     match cli.cmd {
-        Cmd::Memory { action } => match action {
-            MemoryAction::Search { query, limit } => {
-                let results = memory::recall(&query, limit);
-                if results.is_empty() {
-                    eprintln!("no matches for: {query}");
+        Cmd::Completions { shell } => {
+            let mut cmd = Cli::command();
+            clap_complete::generate(shell, &mut cmd, "esc", &mut std::io::stdout());
+        }
+        Cmd::CliSchema => {
+            let schema = command_schema(&Cli::command());
+            println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+        }
+        Cmd::Doctor => {
+            let (report, ok) = doctor::run();
+            println!("{}", serde_json::to_string_pretty(&report).unwrap());
+            if !ok {
+                std::process::exit(EXIT_IO);
+            }
+        }
+        Cmd::Memory { namespace, action } => {
+            let namespace = namespace
+                .or_else(|| config::get().active.namespace.clone())
+                .unwrap_or_else(|| memory::DEFAULT_NAMESPACE.to_string());
+            memory::set_namespace(&namespace);
+            memory_cmd(action);
+        }
+    }
+}
+
+fn memory_cmd(action: MemoryAction) {
+    if matches!(
+        action,
+        MemoryAction::Sync | MemoryAction::Reconcile { .. } | MemoryAction::Purge
+    ) {
+        require_atomic();
+    }
+    match action {
+        MemoryAction::Search {
+            query,
+            limit,
+            filter,
+            page,
+            stream: true,
+            explain,
+        } => {
+            let mut found = 0;
+            let mut out = std::io::stdout();
+            let walked = memory::recall_each(
+                &query,
+                &filter.resolve(),
+                &page.with_limit(limit),
+                explain,
+                |r| {
+                    found += 1;
+                    // A closed pipe means the reader has enough; stop quietly.
+                    writeln!(out, "{r}").is_ok()
+                },
+            );
+            if let Err(e) = walked {
+                fail(EXIT_NOT_FOUND, e);
+            }
+            if found == 0 {
+                log::info!("no matches for: {query}");
+            }
+        }
+        MemoryAction::Search {
+            query,
+            limit,
+            filter,
+            page,
+            explain,
+            ..
+        } => {
+            let results =
+                memory::recall(&query, &filter.resolve(), &page.with_limit(limit), explain)
+                    .unwrap_or_else(|e| fail(EXIT_NOT_FOUND, e));
+            if results.is_empty() {
+                log::info!("no matches for: {query}");
+            }
+            println!("{}", serde_json::to_string_pretty(&results).unwrap());
+        }
+
+        MemoryAction::Show { hash } => {
+            let state = memory::shared();
+            let full_hash = memory::resolve_hash(&state, &hash).unwrap_or_else(|e| fail_lookup(e));
+            let note = &state.notes[&full_hash];
+            let result = serde_json::json!({
+                "type": "note",
+                "hash": full_hash,
+                "kind": note.kind,
+                "summary": note.summary,
+                "detail": note.detail,
+                "context": note.context,
+                "tags": note.tags,
+                "created": note.created,
+                "status": note.status,
+                "namespace": note.namespace,
+                "links": memory::note_links(&state, &full_hash),
+            });
+            println!("{}", serde_json::to_string_pretty(&result).unwrap());
+        }
+
+        MemoryAction::Log {
+            limit,
+            filter,
+            page,
+        } => {
+            let items = memory::log(&filter.resolve(), &page.with_limit(limit))
+                .unwrap_or_else(|e| fail(EXIT_NOT_FOUND, e));
+            if items.is_empty() {
+                log::info!("memory is empty");
+            }
+            println!("{}", serde_json::to_string_pretty(&items).unwrap());
+        }
+
+        MemoryAction::Timeline { since, table } => {
+            let since = match memory::parse_time_bound(&since) {
+                Ok(s) => s,
+                Err(e) => fail(EXIT_VALIDATION, e),
+            };
+            let days = memory::timeline(&since);
+            if days.is_empty() {
+                log::info!("no activity since {since}");
+            }
+            if table {
+                print_timeline_table(&days);
+            } else {
+                println!("{}", serde_json::to_string_pretty(&days).unwrap());
+            }
+        }
+
+        MemoryAction::Graph {
+            output,
+            format,
+            tag,
+            context,
+        } => {
+            let format =
+                format.unwrap_or_else(|| match output.as_ref().and_then(|p| p.extension()) {
+                    Some(ext) if ext == "graphml" => "graphml".to_string(),
+                    _ => "dot".to_string(),
+                });
+            let graph = match memory::export_graph(&format, tag.as_deref(), context.as_deref()) {
+                Ok(g) => g,
+                Err(e) => fail(EXIT_VALIDATION, e),
+            };
+            match output {
+                Some(path) => {
+                    if let Err(e) = std::fs::write(&path, graph) {
+                        fail(EXIT_IO, format!("{}: {e}", path.display()));
+                    }
+                    log::info!("wrote {format} graph to {}", path.display());
                 }
-                println!("{}", serde_json::to_string_pretty(&results).unwrap());
+                None => print!("{graph}"),
             }
+        }
 
-            MemoryAction::Show { hash } => {
-                let state = memory::load();
-                let note = state.notes.into_iter().find(|(h, _)| h.starts_with(&hash));
+        MemoryAction::Note {
+            summary,
+            detail,
+            kind,
+            context,
+            tags,
+            about,
+        } => {
+            let valid_kinds = ["discovery", "decision", "pattern", "issue"];
+            if !valid_kinds.contains(&kind.as_str()) {
+                fail(
+                    EXIT_VALIDATION,
+                    format!("unknown kind: {kind} (valid: {})", valid_kinds.join(", ")),
+                );
+            }
+            let tag_list: Vec<String> = if tags.is_empty() {
+                Vec::new()
+            } else {
+                tags.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            };
+            // Check every link target before writing anything, and link to
+            // the full hashes so the new note can't shadow a short prefix.
+            let hash = memory::note_hash(memory::namespace(), &kind, &summary);
+            let state = memory::shared();
+            let targets: Vec<String> = about
+                .iter()
+                .map(|target| match memory::resolve_hash(&state, target) {
+                    Ok(full) if full == hash => {
+                        fail(EXIT_VALIDATION, "a note cannot link to itself")
+                    }
+                    Ok(full) => full,
+                    Err(e) => fail_lookup(e),
+                })
+                .collect();
+            drop(state);
+            memory::record_note(&kind, &summary, &detail, &context, &tag_list);
+            log::info!("noted [{}]: {} ({})", kind, summary, &hash[..12]);
+            for target in &targets {
+                match memory::link_note(&hash, target, "about") {
+                    Ok(to) => log::info!("  linked about {}", &to[..12]),
+                    Err(e) => fail(EXIT_VALIDATION, e),
+                }
+            }
+        }
 
-                match note {
-                    Some((full_hash, note)) => {
-                        let result = serde_json::json!({
-                            "type": "note",
-                            "hash": full_hash,
+        MemoryAction::Notes {
+            kind,
+            context,
+            limit,
+            page,
+        } => {
+            let notes =
+                memory::list_notes(kind.as_deref(), context.as_deref(), &page.with_limit(limit))
+                    .unwrap_or_else(|e| fail(EXIT_NOT_FOUND, e));
+            if notes.is_empty() {
+                log::info!("no notes");
+                println!("[]");
+            } else {
+                let items: Vec<serde_json::Value> = notes
+                    .into_iter()
+                    .map(|(hash, note)| {
+                        serde_json::json!({
+                            "hash": &hash[..12.min(hash.len())],
                             "kind": note.kind,
                             "summary": note.summary,
                             "detail": note.detail,
@@ -114,131 +516,204 @@ fn main() {
                             "tags": note.tags,
                             "created": note.created,
                             "status": note.status,
-                        });
-                        println!("{}", serde_json::to_string_pretty(&result).unwrap());
-                    }
-                    None => {
-                        eprintln!("not in memory: {hash}");
-                        std::process::exit(1);
-                    }
-                }
+                        })
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&items).unwrap());
             }
+        }
 
-            MemoryAction::Log { limit } => {
-                let items = memory::log(limit);
-                if items.is_empty() {
-                    eprintln!("memory is empty");
-                }
-                println!("{}", serde_json::to_string_pretty(&items).unwrap());
+        MemoryAction::Resolve { hash, status } => {
+            if !STATUSES.contains(&status.as_str()) {
+                fail(
+                    EXIT_VALIDATION,
+                    format!("unknown status: {status} (valid: {})", STATUSES.join(", ")),
+                );
+            }
+            match memory::update_note_status(&hash, &status) {
+                Ok(full) => log::info!("note {} -> {status}", &full[..12]),
+                Err(e) => fail_lookup(e),
             }
+        }
 
-            MemoryAction::Note {
-                summary,
-                detail,
-                kind,
-                context,
-                tags,
-            } => {
-                let valid_kinds = ["discovery", "decision", "pattern", "issue"];
-                if !valid_kinds.contains(&kind.as_str()) {
-                    eprintln!("unknown kind: {kind}");
-                    eprintln!("valid: {}", valid_kinds.join(", "));
-                    std::process::exit(1);
+        MemoryAction::Sync => match memory::sync_to_atomic() {
+            Ok((count, flushed, pending)) => {
+                let total = memory::shared().notes.len();
+                if flushed > 0 || pending > 0 {
+                    log::info!("replayed {flushed} queued writes ({pending} still queued)");
                 }
-                let tag_list: Vec<String> = if tags.is_empty() {
-                    Vec::new()
-                } else {
-                    tags.split(',')
-                        .map(|s| s.trim().to_string())
-                        .filter(|s| !s.is_empty())
-                        .collect()
-                };
-                let hash = memory::note_hash(&kind, &summary);
-                memory::record_note(&kind, &summary, &detail, &context, &tag_list);
-                eprintln!("noted [{}]: {} ({})", kind, summary, &hash[..12]);
-            }
-
-            MemoryAction::Notes {
-                kind,
-                context,
-                limit,
-            } => {
-                let notes = memory::list_notes(kind.as_deref(), context.as_deref(), limit);
-                if notes.is_empty() {
-                    eprintln!("no notes");
-                    println!("[]");
+                log::info!("synced {count}/{total} notes to atomic-server");
+            }
+            Err(e) => fail(EXIT_IO, e),
+        },
+
+        MemoryAction::Reconcile { dry_run } => match memory::reconcile_atomic(dry_run) {
+            Ok(report) => {
+                let count = |k: &str| report[k].as_array().map_or(0, |a| a.len());
+                log::info!(
+                    "{}pulled {}, pushed {}, updated {} local / {} remote, {} conflicts",
+                    if dry_run { "(dry run) " } else { "" },
+                    count("pulled"),
+                    count("pushed"),
+                    count("updated_local"),
+                    count("updated_remote"),
+                    count("conflicts"),
+                );
+                println!("{}", serde_json::to_string_pretty(&report).unwrap());
+            }
+            Err(e) => fail(EXIT_IO, e),
+        },
+
+        MemoryAction::Purge => match memory::purge_atomic() {
+            Ok((deleted, errors)) => {
+                if deleted == 0 && errors == 0 {
+                    log::info!("atomic-server is clean — nothing to purge");
                 } else {
-                    let items: Vec<serde_json::Value> = notes
-                        .into_iter()
-                        .map(|(hash, note)| {
-                            serde_json::json!({
-                                "hash": &hash[..12.min(hash.len())],
-                                "kind": note.kind,
-                                "summary": note.summary,
-                                "detail": note.detail,
-                                "context": note.context,
-                                "tags": note.tags,
-                                "created": note.created,
-                                "status": note.status,
-                            })
-                        })
-                        .collect();
-                    println!("{}", serde_json::to_string_pretty(&items).unwrap());
+                    log::info!("purged {deleted} stale notes ({errors} errors)");
                 }
             }
+            Err(e) => fail(EXIT_IO, e),
+        },
 
-            MemoryAction::Resolve { hash, status } => {
-                let valid = ["resolved", "superseded", "active"];
-                if !valid.contains(&status.as_str()) {
-                    eprintln!("unknown status: {status}");
-                    eprintln!("valid: {}", valid.join(", "));
-                    std::process::exit(1);
-                }
-                memory::update_note_status(&hash, &status);
-                eprintln!("note {} -> {status}", &hash[..12.min(hash.len())]);
+        MemoryAction::Setup => {
+            let client = require_atomic();
+            match client.ensure_schema() {
+                Ok(()) => log::info!("schema created on {}", client.server_url),
+                Err(e) => fail(EXIT_IO, e),
             }
+        }
 
-            MemoryAction::Sync => match memory::sync_to_atomic() {
-                Ok(count) => {
-                    let state = memory::load();
-                    eprintln!(
-                        "synced {count}/{} notes to atomic-server",
-                        state.notes.len()
+        MemoryAction::Ns { action } => match action {
+            NsAction::List => {
+                let items = memory::list_namespaces();
+                println!("{}", serde_json::to_string_pretty(&items).unwrap());
+            }
+            NsAction::Merge { from, into } => {
+                let into = into.unwrap_or_else(|| memory::namespace().to_string());
+                if from == into {
+                    fail(
+                        EXIT_VALIDATION,
+                        format!("cannot merge namespace '{from}' into itself"),
                     );
                 }
-                Err(e) => {
-                    eprintln!("error: {e}");
-                    std::process::exit(1);
-                }
-            },
-
-            MemoryAction::Purge => match memory::purge_atomic() {
-                Ok((deleted, errors)) => {
-                    if deleted == 0 && errors == 0 {
-                        eprintln!("atomic-server is clean — nothing to purge");
-                    } else {
-                        eprintln!("purged {deleted} stale notes ({errors} errors)");
-                    }
-                }
-                Err(e) => {
-                    eprintln!("error: {e}");
-                    std::process::exit(1);
-                }
-            },
-
-            MemoryAction::Setup => match atomic::AtomicClient::from_env() {
-                Some(client) => match client.ensure_schema() {
-                    Ok(()) => eprintln!("schema created on {}", client.server_url),
-                    Err(e) => {
-                        eprintln!("error: {e}");
-                        std::process::exit(1);
+                match memory::merge_namespace(&from, &into) {
+                    Ok((moved, merged)) => {
+                        log::info!("merged {from} -> {into}: {moved} moved, {merged} merged")
                     }
-                },
-                None => {
-                    eprintln!("error: set ESC_ATOMIC_URL and ESC_ATOMIC_KEY");
-                    std::process::exit(1);
+                    Err(e) => fail(EXIT_NOT_FOUND, e),
                 }
-            },
+            }
         },
     }
 }
+
+fn print_timeline_table(days: &[serde_json::Value]) {
+    println!("{:<10}  {:>5}  {:>6}  KINDS", "DAY", "NOTES", "CLOSED");
+    for day in days {
+        let kinds: Vec<String> = day["by_kind"]
+            .as_object()
+            .map(|m| m.iter().map(|(k, v)| format!("{k}:{v}")).collect())
+            .unwrap_or_default();
+        println!(
+            "{:<10}  {:>5}  {:>6}  {}",
+            day["day"].as_str().unwrap_or(""),
+            day["notes"].as_u64().unwrap_or(0),
+            day["closed"].as_u64().unwrap_or(0),
+            kinds.join(" ")
+        );
+        for h in day["highlights"].as_array().into_iter().flatten() {
+            println!(
+                "            {:<14} {}  {}",
+                h["event"].as_str().unwrap_or(""),
+                h["hash"].as_str().unwrap_or(""),
+                h["summary"].as_str().unwrap_or("")
+            );
+        }
+    }
+}
+
+/// Describe a clap command (and its subcommands) as JSON, for agent
+/// frameworks that generate function-call schemas.
+fn command_schema(cmd: &clap::Command) -> serde_json::Value {
+    let args: Vec<serde_json::Value> = cmd
+        .get_arguments()
+        .filter(|a| !a.is_hide_set() && a.get_id() != "help" && a.get_id() != "version")
+        .map(|a| {
+            let takes_value = a.get_action().takes_values();
+            let possible: Vec<String> = a
+                .get_possible_values()
+                .iter()
+                .map(|v| v.get_name().to_string())
+                .collect();
+            let defaults: Vec<String> = a
+                .get_default_values()
+                .iter()
+                .map(|v| v.to_string_lossy().into_owned())
+                .collect();
+            serde_json::json!({
+                "name": a.get_id().as_str(),
+                "long": a.get_long(),
+                "short": a.get_short().map(|c| c.to_string()),
+                "positional": a.is_positional(),
+                "help": a.get_help().map(|h| h.to_string()),
+                "required": a.is_required_set(),
+                "takes_value": takes_value,
+                "multiple": takes_value && matches!(a.get_action(), clap::ArgAction::Append),
+                "default": defaults.first(),
+                "env": a.get_env().map(|e| e.to_string_lossy().into_owned()),
+                "possible_values": possible,
+                "global": a.is_global_set(),
+            })
+        })
+        .collect();
+    let subcommands: Vec<serde_json::Value> = cmd
+        .get_subcommands()
+        .filter(|c| c.get_name() != "help")
+        .map(command_schema)
+        .collect();
+    serde_json::json!({
+        "name": cmd.get_name(),
+        "about": cmd.get_about().map(|a| a.to_string()),
+        "args": args,
+        "subcommands": subcommands,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter_args(args: &[&str]) -> FilterArgs {
+        let cli = Cli::try_parse_from(args).expect("valid command line");
+        match cli.cmd {
+            Cmd::Memory {
+                action: MemoryAction::Log { filter, .. } | MemoryAction::Search { filter, .. },
+                ..
+            } => filter,
+            _ => panic!("not a log/search command"),
+        }
+    }
+
+    #[test]
+    fn bad_filters_are_validation_errors() {
+        let cases: [&[&str]; 4] = [
+            &["esc", "memory", "log", "--since", "é"],
+            &["esc", "memory", "log", "--until", "7д"],
+            &["esc", "memory", "search", "q", "--since", "abcd-ef-ghij"],
+            &["esc", "memory", "search", "q", "--status", "open"],
+        ];
+        for args in cases {
+            assert!(filter_args(args).parse().is_err(), "{args:?}");
+        }
+        let ok = filter_args(&[
+            "esc",
+            "memory",
+            "log",
+            "--since",
+            "7d",
+            "--until",
+            "2026-02-01",
+        ]);
+        assert!(ok.parse().is_ok());
+    }
+}
@@ -1,23 +1,47 @@
 //! Memory graph — persistent contextual knowledge across sessions.
 //!
-//! The memory is stored at `~/.esc/memory.json`:
+//! The memory is stored at `~/.esc/memory.json` (or under `ESC_HOME`):
 //! - **Notes**: contextual knowledge (discoveries, decisions, patterns, issues)
+//! - **Links**: typed note→note edges (`esc memory note --about <hash>`)
 //!
 //! Queried through `esc memory search/show/note` subcommands.
+//! Notes live in a namespace (`--namespace` / `ESC_MEMORY_NS`, default "default");
+//! list, log and search only see the active one.
 //! Dual-writes to atomic-server when configured (proper /query endpoint for structured lookups).
+//! Failed dual-writes are queued in `~/.esc/atomic_outbox.jsonl` and replayed by `esc memory sync`.
 
+use crate::log;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, OnceLock};
 
-/// Top-level memory state — notes only.
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+static NAMESPACE: OnceLock<String> = OnceLock::new();
+/// memory.json, parsed on first use and kept in sync by `save`.
+static STATE: OnceLock<Mutex<MemoryState>> = OnceLock::new();
+
+/// Top-level memory state — notes and the links between them.
 /// Serde will silently ignore unknown fields (entries, edges) from old files.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryState {
     pub version: u32,
     #[serde(default)]
     pub notes: HashMap<String, MemoryNote>,
+    #[serde(default)]
+    pub links: Vec<NoteLink>,
+}
+
+/// Directed, typed edge between two notes (full hashes).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteLink {
+    pub from: String,
+    pub to: String,
+    /// Relation: about
+    pub relation: String,
+    pub created: String,
 }
 
 /// Contextual knowledge entry — discoveries, decisions, patterns, issues.
@@ -38,29 +62,83 @@ pub struct MemoryNote {
     pub created: String,
     #[serde(default = "default_note_status")]
     pub status: String,
+    /// Last modification (detail/tags/status), empty if never changed
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub updated: String,
+    /// Memory namespace (project, experiment, agent)
+    #[serde(
+        default = "default_namespace",
+        skip_serializing_if = "is_default_namespace"
+    )]
+    pub namespace: String,
 }
 
 fn default_note_status() -> String {
     "active".to_string()
 }
 
+fn default_namespace() -> String {
+    DEFAULT_NAMESPACE.to_string()
+}
+
+fn is_default_namespace(ns: &String) -> bool {
+    ns == DEFAULT_NAMESPACE
+}
+
+/// Select the active namespace for this process. First call wins.
+pub fn set_namespace(ns: &str) {
+    let ns = if ns.is_empty() { DEFAULT_NAMESPACE } else { ns };
+    let _ = NAMESPACE.set(ns.to_string());
+}
+
+/// Active namespace (defaults to "default").
+pub fn namespace() -> &'static str {
+    NAMESPACE
+        .get()
+        .map(|s| s.as_str())
+        .unwrap_or(DEFAULT_NAMESPACE)
+}
+
+fn in_namespace(note: &MemoryNote) -> bool {
+    note.namespace == namespace()
+}
+
 fn memory_path() -> PathBuf {
-    let home = std::env::var("HOME")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from("."));
-    home.join(".esc").join("memory.json")
+    crate::config::esc_home().join("memory.json")
+}
+
+fn outbox_path() -> PathBuf {
+    crate::config::esc_home().join("atomic_outbox.jsonl")
+}
+
+/// Shared read access to the memory state, which is read from disk once per
+/// process. The lock is not reentrant: drop the guard before calling `load`,
+/// `save` or `shared` again.
+pub fn shared() -> MutexGuard<'static, MemoryState> {
+    STATE
+        .get_or_init(|| Mutex::new(read_state()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
 }
 
+/// Owned copy of the memory state, for changes written back with `save`.
 pub fn load() -> MemoryState {
+    shared().clone()
+}
+
+fn read_state() -> MemoryState {
     let path = memory_path();
+    log::debug!("load {}", path.display());
     match fs::read_to_string(&path) {
         Ok(s) => serde_json::from_str(&s).unwrap_or_else(|_| MemoryState {
             version: 1,
             notes: HashMap::new(),
+            links: Vec::new(),
         }),
         Err(_) => MemoryState {
             version: 1,
             notes: HashMap::new(),
+            links: Vec::new(),
         },
     }
 }
@@ -72,17 +150,29 @@ fn save(state: &MemoryState) {
     }
     let json = serde_json::to_string_pretty(state).unwrap_or_default();
     let tmp = path.with_extension("tmp");
-    if fs::write(&tmp, &json).is_ok() {
-        let _ = fs::rename(&tmp, &path);
+    match fs::write(&tmp, &json).and_then(|_| fs::rename(&tmp, &path)) {
+        Ok(()) => log::debug!("saved {} notes to {}", state.notes.len(), path.display()),
+        Err(e) => log::error!("{}: {e}", path.display()),
+    }
+    match STATE.get() {
+        Some(cached) => *cached.lock().unwrap_or_else(|e| e.into_inner()) = state.clone(),
+        None => {
+            let _ = STATE.set(Mutex::new(state.clone()));
+        }
     }
 }
 
 // --- Notes ---
 
 /// Compute content-addressed hash for a note (deduplicates same kind+summary).
-pub fn note_hash(kind: &str, summary: &str) -> String {
+/// Non-default namespaces are mixed in so the same note can live in several.
+pub fn note_hash(namespace: &str, kind: &str, summary: &str) -> String {
     use sha2::{Digest, Sha256};
     let mut hasher = Sha256::new();
+    if namespace != DEFAULT_NAMESPACE {
+        hasher.update(namespace.as_bytes());
+        hasher.update(b"/");
+    }
     hasher.update(kind.as_bytes());
     hasher.update(b":");
     hasher.update(summary.as_bytes());
@@ -91,11 +181,12 @@ pub fn note_hash(kind: &str, summary: &str) -> String {
 
 /// Record a contextual note. Dual-writes to atomic-server when configured.
 pub fn record_note(kind: &str, summary: &str, detail: &str, context: &str, tags: &[String]) {
-    let hash = note_hash(kind, summary);
+    let hash = note_hash(namespace(), kind, summary);
     let mut state = load();
     let now = now_rfc3339();
 
     if let Some(existing) = state.notes.get_mut(&hash) {
+        existing.updated = now;
         if !detail.is_empty() {
             existing.detail = detail.to_string();
         }
@@ -118,6 +209,8 @@ pub fn record_note(kind: &str, summary: &str, detail: &str, context: &str, tags:
                 tags: tags.to_vec(),
                 created: now,
                 status: "active".to_string(),
+                updated: String::new(),
+                namespace: namespace().to_string(),
             },
         );
     }
@@ -127,69 +220,275 @@ pub fn record_note(kind: &str, summary: &str, detail: &str, context: &str, tags:
     // Dual-write to atomic-server
     if let Some(client) = crate::atomic::AtomicClient::from_env() {
         let note = state.notes.get(&hash).unwrap();
-        let _ = atomic_record_note(&client, &hash, note);
+        dual_write(&client, &hash, note);
+    }
+}
+
+/// Why a hash prefix did not pick out exactly one note.
+#[derive(Debug, PartialEq)]
+pub enum HashLookupError {
+    Empty,
+    NotFound(String),
+    Ambiguous(String, usize),
+}
+
+impl std::fmt::Display for HashLookupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HashLookupError::Empty => write!(f, "empty note hash"),
+            HashLookupError::NotFound(prefix) => write!(f, "not in memory: {prefix}"),
+            HashLookupError::Ambiguous(prefix, n) => {
+                write!(f, "ambiguous hash prefix: {prefix} matches {n} notes")
+            }
+        }
+    }
+}
+
+/// Resolve a hash prefix to the one full note hash it matches.
+pub fn resolve_hash(state: &MemoryState, prefix: &str) -> Result<String, HashLookupError> {
+    if prefix.is_empty() {
+        return Err(HashLookupError::Empty);
     }
+    if state.notes.contains_key(prefix) {
+        return Ok(prefix.to_string());
+    }
+    let mut matches = state.notes.keys().filter(|k| k.starts_with(prefix));
+    match (matches.next(), matches.count()) {
+        (None, _) => Err(HashLookupError::NotFound(prefix.to_string())),
+        (Some(hash), 0) => Ok(hash.clone()),
+        (Some(_), more) => Err(HashLookupError::Ambiguous(prefix.to_string(), more + 1)),
+    }
+}
+
+/// Link a note to another note. Both hashes may be prefixes; returns the
+/// resolved target hash. Re-linking the same pair is a no-op.
+pub fn link_note(from: &str, to: &str, relation: &str) -> Result<String, String> {
+    let mut state = load();
+    let resolve = |prefix: &str| resolve_hash(&state, prefix).map_err(|e| e.to_string());
+    let from = resolve(from)?;
+    let to = resolve(to)?;
+    if from == to {
+        return Err("a note cannot link to itself".to_string());
+    }
+    let exists = state
+        .links
+        .iter()
+        .any(|l| l.from == from && l.to == to && l.relation == relation);
+    if !exists {
+        state.links.push(NoteLink {
+            from,
+            to: to.clone(),
+            relation: relation.to_string(),
+            created: now_rfc3339(),
+        });
+        save(&state);
+    }
+    Ok(to)
+}
+
+/// Links touching a note, as compact JSON (direction, relation, other end).
+pub fn note_links(state: &MemoryState, hash: &str) -> Vec<serde_json::Value> {
+    let mut items = Vec::new();
+    for link in &state.links {
+        let (direction, other) = if link.from == hash {
+            ("out", &link.to)
+        } else if link.to == hash {
+            ("in", &link.from)
+        } else {
+            continue;
+        };
+        let summary = state
+            .notes
+            .get(other)
+            .map(|n| n.summary.as_str())
+            .unwrap_or("(missing)");
+        items.push(serde_json::json!({
+            "direction": direction,
+            "relation": link.relation,
+            "hash": &other[..12.min(other.len())],
+            "summary": summary,
+        }));
+    }
+    items
 }
 
 /// Update a note's status (active → resolved/superseded).
-pub fn update_note_status(hash: &str, status: &str) {
+/// Returns the full hash the prefix resolved to.
+pub fn update_note_status(hash: &str, status: &str) -> Result<String, HashLookupError> {
     let mut state = load();
-    let full_hash = state.notes.keys().find(|k| k.starts_with(hash)).cloned();
-    if let Some(full_hash) = full_hash {
-        if let Some(note) = state.notes.get_mut(&full_hash) {
-            note.status = status.to_string();
-            let snapshot = note.clone();
-            save(&state);
-            if let Some(client) = crate::atomic::AtomicClient::from_env() {
-                let _ = atomic_record_note(&client, &full_hash, &snapshot);
+    let full_hash = resolve_hash(&state, hash)?;
+    if let Some(note) = state.notes.get_mut(&full_hash) {
+        note.status = status.to_string();
+        note.updated = now_rfc3339();
+        let snapshot = note.clone();
+        save(&state);
+        if let Some(client) = crate::atomic::AtomicClient::from_env() {
+            dual_write(&client, &full_hash, &snapshot);
+        }
+    }
+    Ok(full_hash)
+}
+
+/// Window over an ordered result list: skip `offset` items, or everything
+/// up to and including the item whose hash starts with `cursor`, then take
+/// `limit`. Orders are total (ties broken by hash), so pages are stable.
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub limit: usize,
+    pub offset: usize,
+    pub cursor: Option<String>,
+}
+
+impl Page {
+    /// Apply the window to an ordered iterator, feeding kept items to `emit`
+    /// until it returns false. Fails if the cursor never shows up.
+    fn walk<T>(
+        &self,
+        items: impl IntoIterator<Item = T>,
+        hash: impl Fn(&T) -> &str,
+        mut emit: impl FnMut(T) -> bool,
+    ) -> Result<(), String> {
+        if self.limit == 0 {
+            return Ok(());
+        }
+        let mut started = self.cursor.is_none();
+        let mut skipped = 0;
+        let mut taken = 0;
+        for item in items {
+            if !started {
+                let cursor = self.cursor.as_deref().unwrap_or_default();
+                started = !cursor.is_empty() && hash(&item).starts_with(cursor);
+                continue;
+            }
+            if skipped < self.offset {
+                skipped += 1;
+                continue;
             }
+            taken += 1;
+            // Stop before pulling another item: the iterator may be lazy
+            // (atomic-server is only queried once local results run out).
+            if !emit(item) || taken == self.limit {
+                return Ok(());
+            }
+        }
+        match &self.cursor {
+            Some(cursor) if !started => Err(format!("cursor not in results: {cursor}")),
+            _ => Ok(()),
         }
     }
+
+    /// Whether `hashes` already reach past the end of this page, so a
+    /// longer fetch could not change what `walk` emits.
+    fn covered(&self, hashes: &[&str]) -> bool {
+        let start = match self.cursor.as_deref() {
+            None => 0,
+            Some(cursor) => {
+                match hashes
+                    .iter()
+                    .position(|h| !cursor.is_empty() && h.starts_with(cursor))
+                {
+                    Some(pos) => pos + 1,
+                    None => return false,
+                }
+            }
+        };
+        hashes.len() >= start + self.offset + self.limit
+    }
+
+    fn collect<T>(
+        &self,
+        items: impl IntoIterator<Item = T>,
+        hash: impl Fn(&T) -> &str,
+    ) -> Result<Vec<T>, String> {
+        let mut out = Vec::new();
+        self.walk(items, hash, |item| {
+            out.push(item);
+            true
+        })?;
+        Ok(out)
+    }
+}
+
+/// Note filters for `log` and `search`. Time bounds are RFC 3339 prefixes
+/// from `parse_time_bound`; `until` includes the instant (or day) it names.
+#[derive(Debug, Clone, Default)]
+pub struct NoteFilter {
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub status: Option<String>,
+    /// Every one of these tags must be present
+    pub tags: Vec<String>,
+}
+
+impl NoteFilter {
+    /// Check everything but status, whose default differs per command.
+    fn matches_ignoring_status(&self, note: &MemoryNote) -> bool {
+        if let Some(since) = &self.since
+            && note.created.as_str() < since.as_str()
+        {
+            return false;
+        }
+        if let Some(until) = &self.until {
+            let created = note.created.get(..until.len()).unwrap_or(&note.created);
+            if created > until.as_str() {
+                return false;
+            }
+        }
+        self.tags.iter().all(|t| note.tags.contains(t))
+    }
+
+    /// Status defaults to any.
+    fn matches(&self, note: &MemoryNote) -> bool {
+        self.matches_ignoring_status(note) && self.status.as_ref().is_none_or(|s| *s == note.status)
+    }
 }
 
-/// List notes with optional kind/context filters.
+/// List notes with optional kind/context filters, newest first.
 /// Local file is the source of truth.
 pub fn list_notes(
     kind: Option<&str>,
     context: Option<&str>,
-    limit: usize,
-) -> Vec<(String, MemoryNote)> {
-    local_list_notes(kind, context, limit)
+    page: &Page,
+) -> Result<Vec<(String, MemoryNote)>, String> {
+    page.collect(local_list_notes(kind, context), |(hash, _)| hash)
 }
 
-fn local_list_notes(
-    kind: Option<&str>,
-    context: Option<&str>,
-    limit: usize,
-) -> Vec<(String, MemoryNote)> {
-    let state = load();
+fn local_list_notes(kind: Option<&str>, context: Option<&str>) -> Vec<(String, MemoryNote)> {
+    let state = shared();
     let mut notes: Vec<(String, MemoryNote)> = state
         .notes
-        .into_iter()
+        .iter()
         .filter(|(_, n)| {
-            (kind.is_none() || kind == Some(n.kind.as_str()))
+            in_namespace(n)
+                && (kind.is_none() || kind == Some(n.kind.as_str()))
                 && (context.is_none() || context == Some(n.context.as_str()))
         })
+        .map(|(h, n)| (h.clone(), n.clone()))
         .collect();
-    notes.sort_by(|a, b| b.1.created.cmp(&a.1.created));
-    notes.truncate(limit);
+    notes.sort_by(|a, b| b.1.created.cmp(&a.1.created).then_with(|| a.0.cmp(&b.0)));
     notes
 }
 
 // --- Log ---
 
-/// Activity log: notes by time, newest first.
-/// Local file is the source of truth.
-pub fn log(limit: usize) -> Vec<serde_json::Value> {
-    local_log(limit)
+/// Activity log: notes by time, newest first. Without a status filter only
+/// open notes are listed. Local file is the source of truth.
+pub fn log(filter: &NoteFilter, page: &Page) -> Result<Vec<serde_json::Value>, String> {
+    page.collect(local_log(filter), |item| {
+        item["hash"].as_str().unwrap_or("")
+    })
 }
 
-fn local_log(limit: usize) -> Vec<serde_json::Value> {
-    let state = load();
+fn local_log(filter: &NoteFilter) -> Vec<serde_json::Value> {
+    let state = shared();
     let mut items: Vec<serde_json::Value> = Vec::new();
 
     for (hash, note) in &state.notes {
-        if note.status == "resolved" || note.status == "superseded" {
+        let status_ok = match &filter.status {
+            Some(status) => *status == note.status,
+            None => note.status != "resolved" && note.status != "superseded",
+        };
+        if !in_namespace(note) || !status_ok || !filter.matches_ignoring_status(note) {
             continue;
         }
         items.push(serde_json::json!({
@@ -206,52 +505,121 @@ fn local_log(limit: usize) -> Vec<serde_json::Value> {
         let ta = a["time"].as_str().unwrap_or("");
         let tb = b["time"].as_str().unwrap_or("");
         tb.cmp(ta)
+            .then_with(|| a["hash"].as_str().cmp(&b["hash"].as_str()))
     });
-    items.truncate(limit);
     items
 }
 
 // --- Recall (search) ---
 
 /// Recall: search notes. Compact output — LLM drills in with `show`.
-pub fn recall(query: &str, limit: usize) -> Vec<serde_json::Value> {
+pub fn recall(
+    query: &str,
+    filter: &NoteFilter,
+    page: &Page,
+    explain: bool,
+) -> Result<Vec<serde_json::Value>, String> {
+    let mut results = Vec::new();
+    recall_each(query, filter, page, explain, |r| {
+        results.push(r);
+        true
+    })?;
+    Ok(results)
+}
+
+/// Like `recall`, but hands each result to `emit` as soon as it is known:
+/// local matches first, then any extra atomic-server hits. Stops early when
+/// `emit` returns false. With `explain`, each result carries an `explain`
+/// object saying why it ranked where it did.
+pub fn recall_each(
+    query: &str,
+    filter: &NoteFilter,
+    page: &Page,
+    explain: bool,
+    emit: impl FnMut(serde_json::Value) -> bool,
+) -> Result<(), String> {
     // Always search local first — it's the source of truth.
     // Atomic-server is a sync target, not the primary store.
-    let mut results = local_recall(query, limit);
+    let local = local_recall(query, filter, explain);
+    let mut seen: std::collections::HashSet<String> = local
+        .iter()
+        .filter_map(|r| r["hash"].as_str().map(|s| s.to_string()))
+        .collect();
 
-    // Supplement with atomic-server results (may find notes not yet in local file)
-    if let Some(client) = crate::atomic::AtomicClient::from_env() {
-        match atomic_recall(&client, query, limit) {
-            Ok(remote) => {
-                let seen: std::collections::HashSet<String> = results
-                    .iter()
-                    .filter_map(|r| r["hash"].as_str().map(|s| s.to_string()))
-                    .collect();
-                for r in remote {
-                    if let Some(h) = r["hash"].as_str() {
-                        if !seen.contains(h) {
-                            results.push(r);
-                        }
-                    }
+    // Supplement with atomic-server results (may find notes not yet in local
+    // file). Only queried if the page reaches past the local matches. The
+    // search has no offset, so grow the fetch until it covers the page
+    // (a cursor may sit anywhere in the remote tail) or the server runs dry.
+    let local_hashes: Vec<String> = local
+        .iter()
+        .map(|r| r["hash"].as_str().unwrap_or("").to_string())
+        .collect();
+    let remote = std::iter::once_with(move || {
+        let Some(client) = crate::atomic::AtomicClient::from_env() else {
+            return Vec::new();
+        };
+        let mut fetch = local_hashes.len() + page.offset + page.limit;
+        let fresh: Vec<serde_json::Value> = loop {
+            let (remote, capped) = match atomic_recall(&client, query, filter, fetch) {
+                Ok(found) => found,
+                Err(e) => {
+                    log::warn!("atomic-server: {e}");
+                    return Vec::new();
                 }
+            };
+            let fresh: Vec<serde_json::Value> = remote
+                .into_iter()
+                .filter(|r| r["hash"].as_str().is_some_and(|h| !seen.contains(h)))
+                .collect();
+            let hashes: Vec<&str> = local_hashes
+                .iter()
+                .map(String::as_str)
+                .chain(fresh.iter().map(|r| r["hash"].as_str().unwrap_or("")))
+                .collect();
+            if !capped || page.covered(&hashes) {
+                break fresh;
             }
-            Err(e) => eprintln!("warning: atomic-server: {e}"),
-        }
-    }
+            fetch *= 2;
+        };
+        fresh
+            .into_iter()
+            .filter(|r| {
+                r["hash"]
+                    .as_str()
+                    .is_some_and(|h| seen.insert(h.to_string()))
+            })
+            .map(|mut r| {
+                if explain {
+                    r["explain"] = serde_json::json!({ "source": "atomic-server full-text" });
+                }
+                r
+            })
+            .collect()
+    })
+    .flatten();
 
-    results.truncate(limit);
-    results
+    page.walk(
+        local.into_iter().chain(remote),
+        |r| r["hash"].as_str().unwrap_or(""),
+        emit,
+    )
 }
 
-fn local_recall(query: &str, limit: usize) -> Vec<serde_json::Value> {
+/// All local matches, best first: direct hits by score, then tag expansion.
+fn local_recall(query: &str, filter: &NoteFilter, explain: bool) -> Vec<serde_json::Value> {
     use std::collections::HashSet;
 
     let mut results: Vec<serde_json::Value> = Vec::new();
     let mut seen: HashSet<String> = HashSet::new();
     let mut all_tags: HashSet<String> = HashSet::new();
+    // (short hash, tags) of direct hits, to say which ones an expansion came from
+    let mut direct: Vec<(String, Vec<String>)> = Vec::new();
 
     // Phase 1: Direct search
     for (hash, note, score) in local_search_notes(query) {
+        if !filter.matches(&note) {
+            continue;
+        }
         let short = hash[..12.min(hash.len())].to_string();
         for tag in &note.tags {
             all_tags.insert(tag.clone());
@@ -259,20 +627,26 @@ fn local_recall(query: &str, limit: usize) -> Vec<serde_json::Value> {
         seen.insert(hash);
         let mut r = compact_note(&short, &note, "direct");
         r["score"] = serde_json::json!(score);
+        if explain {
+            r["explain"] = explain_score(&note, query);
+            direct.push((short, note.tags.clone()));
+        }
         results.push(r);
     }
 
     // Phase 2: Tag expansion (2+ shared tags, capped)
     if !all_tags.is_empty() {
-        let state = load();
+        let state = shared();
         let mut tag_expanded = 0usize;
         const MAX_TAG_EXPANSION: usize = 5;
 
-        for (hash, note) in &state.notes {
+        let mut candidates: Vec<(&String, &MemoryNote)> = state.notes.iter().collect();
+        candidates.sort_by_key(|(hash, _)| *hash);
+        for (hash, note) in candidates {
             if tag_expanded >= MAX_TAG_EXPANSION {
                 break;
             }
-            if seen.contains(hash) {
+            if seen.contains(hash) || !in_namespace(note) || !filter.matches(note) {
                 continue;
             }
             let shared: Vec<&str> = note
@@ -284,11 +658,19 @@ fn local_recall(query: &str, limit: usize) -> Vec<serde_json::Value> {
             if shared.len() >= 2 {
                 seen.insert(hash.clone());
                 let short = &hash[..12.min(hash.len())];
-                results.push(compact_note(
-                    short,
-                    note,
-                    &format!("shared_tags:{}", shared.join(",")),
-                ));
+                let mut r = compact_note(short, note, &format!("shared_tags:{}", shared.join(",")));
+                if explain {
+                    let from: Vec<&str> = direct
+                        .iter()
+                        .filter(|(_, tags)| tags.iter().any(|t| shared.contains(&t.as_str())))
+                        .map(|(h, _)| h.as_str())
+                        .collect();
+                    r["explain"] = serde_json::json!({
+                        "shared_tags": shared,
+                        "expanded_from": from,
+                    });
+                }
+                results.push(r);
                 tag_expanded += 1;
             }
         }
@@ -298,63 +680,106 @@ fn local_recall(query: &str, limit: usize) -> Vec<serde_json::Value> {
         let sa = a["score"].as_u64().unwrap_or(0);
         let sb = b["score"].as_u64().unwrap_or(0);
         sb.cmp(&sa)
+            .then_with(|| a["hash"].as_str().cmp(&b["hash"].as_str()))
     });
-
-    results.truncate(limit);
     results
 }
 
 fn local_search_notes(query: &str) -> Vec<(String, MemoryNote, usize)> {
-    let state = load();
+    let state = shared();
     let query_lower = query.to_lowercase();
     let query_words: Vec<&str> = query_lower.split_whitespace().collect();
 
     let mut results: Vec<(String, MemoryNote, usize)> = state
         .notes
-        .into_iter()
+        .iter()
+        .filter(|(_, note)| in_namespace(note))
         .filter_map(|(hash, note)| {
-            let score = score_note(&note, &query_words);
+            let score = score_note(note, &query_words);
             if score > 0 {
-                Some((hash, note, score))
+                Some((hash.clone(), note.clone(), score))
             } else {
                 None
             }
         })
         .collect();
 
-    results.sort_by(|a, b| b.2.cmp(&a.2));
+    results.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
     results
 }
 
+/// Keyword score plus a recency bonus for matching notes.
+/// Weights come from `[recall]` in config.toml.
 fn score_note(note: &MemoryNote, query_words: &[&str]) -> usize {
-    let mut score = 0;
+    score_breakdown(note, query_words).0
+}
+
+/// Score with its parts: `(total, [(word, field, points)], recency bonus)`.
+fn score_breakdown<'w>(
+    note: &MemoryNote,
+    query_words: &[&'w str],
+) -> (usize, Vec<(&'w str, &'static str, usize)>, usize) {
+    let w = &crate::config::get().recall;
+    let mut hits = Vec::new();
     let summary_lower = note.summary.to_lowercase();
     let detail_lower = note.detail.to_lowercase();
     let kind_lower = note.kind.to_lowercase();
     let context_lower = note.context.to_lowercase();
     let tags_lower: Vec<String> = note.tags.iter().map(|t| t.to_lowercase()).collect();
 
-    for word in query_words {
+    for &word in query_words {
         if summary_lower.contains(word) {
-            score += 3;
+            hits.push((word, "summary", w.summary));
         }
         if detail_lower.contains(word) {
-            score += 2;
+            hits.push((word, "detail", w.detail));
         }
-        if kind_lower == *word {
-            score += 2;
+        if kind_lower == word {
+            hits.push((word, "kind", w.kind));
         }
         if context_lower.contains(word) {
-            score += 2;
+            hits.push((word, "context", w.context));
         }
         if tags_lower.iter().any(|t| t == word) {
-            score += 3;
+            hits.push((word, "tag_exact", w.tag_exact));
         }
         if tags_lower.iter().any(|t| t.contains(word)) {
-            score += 1;
+            hits.push((word, "tag_partial", w.tag_partial));
         }
     }
-    score
+    let keyword: usize = hits.iter().map(|h| h.2).sum();
+    let recency = if keyword > 0 {
+        recency_bonus(&note.created, w)
+    } else {
+        0
+    };
+    (keyword + recency, hits, recency)
+}
+
+/// `--explain` payload for a direct hit: which words matched which fields.
+fn explain_score(note: &MemoryNote, query: &str) -> serde_json::Value {
+    let query_lower = query.to_lowercase();
+    let query_words: Vec<&str> = query_lower.split_whitespace().collect();
+    let (total, hits, recency) = score_breakdown(note, &query_words);
+    let matches: Vec<serde_json::Value> = hits
+        .iter()
+        .map(|(word, field, points)| {
+            serde_json::json!({ "word": word, "field": field, "points": points })
+        })
+        .collect();
+    serde_json::json!({ "matches": matches, "recency": recency, "total": total })
+}
+
+fn recency_bonus(created: &str, w: &crate::config::RecallWeights) -> usize {
+    let Some(created) = parse_rfc3339(created) else {
+        return 0;
+    };
+    if w.recency == 0 || w.recency_half_life_days <= 0.0 {
+        return 0;
+    }
+    let age_days = now_secs().saturating_sub(created) as f64 / 86400.0;
+    let bonus = w.recency as f64 * 0.5f64.powf(age_days / w.recency_half_life_days);
+    bonus.round() as usize
 }
 
 /// Compact note representation.
@@ -370,31 +795,659 @@ fn compact_note(hash: &str, note: &MemoryNote, via: &str) -> serde_json::Value {
     })
 }
 
+// --- Namespaces ---
+
+/// Note counts per namespace, sorted by name.
+pub fn list_namespaces() -> Vec<serde_json::Value> {
+    let state = shared();
+    let mut counts: std::collections::BTreeMap<&str, (usize, usize)> =
+        std::collections::BTreeMap::new();
+    for note in state.notes.values() {
+        let c = counts.entry(note.namespace.as_str()).or_default();
+        c.0 += 1;
+        if note.status == "active" {
+            c.1 += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(ns, (total, active))| {
+            serde_json::json!({
+                "namespace": ns,
+                "notes": total,
+                "active": active,
+                "current": ns == namespace(),
+            })
+        })
+        .collect()
+}
+
+/// In-memory half of `merge_namespace`: re-keys notes and repoints links,
+/// dropping links that end up duplicated or self-referencing. Returns
+/// (moved, merged, touched (old, new) hash pairs).
+fn merge_notes(
+    state: &mut MemoryState,
+    from: &str,
+    into: &str,
+) -> (usize, usize, Vec<(String, String)>) {
+    let sources: Vec<String> = state
+        .notes
+        .iter()
+        .filter(|(_, n)| n.namespace == from)
+        .map(|(h, _)| h.clone())
+        .collect();
+
+    let mut moved = 0;
+    let mut merged = 0;
+    let mut touched: Vec<(String, String)> = Vec::new();
+
+    for old_hash in sources {
+        let mut note = state.notes.remove(&old_hash).unwrap();
+        note.namespace = into.to_string();
+        let new_hash = note_hash(into, &note.kind, &note.summary);
+
+        if let Some(existing) = state.notes.get_mut(&new_hash) {
+            if !note.detail.is_empty() {
+                existing.detail = note.detail;
+            }
+            if !note.context.is_empty() {
+                existing.context = note.context;
+            }
+            for tag in note.tags {
+                if !existing.tags.contains(&tag) {
+                    existing.tags.push(tag);
+                }
+            }
+            if note.created < existing.created {
+                existing.created = note.created;
+            }
+            merged += 1;
+        } else {
+            state.notes.insert(new_hash.clone(), note);
+            moved += 1;
+        }
+        touched.push((old_hash, new_hash));
+    }
+
+    for (old_hash, new_hash) in &touched {
+        for link in state.links.iter_mut() {
+            if &link.from == old_hash {
+                link.from = new_hash.clone();
+            }
+            if &link.to == old_hash {
+                link.to = new_hash.clone();
+            }
+        }
+    }
+    let mut links: Vec<NoteLink> = Vec::new();
+    for link in std::mem::take(&mut state.links) {
+        let dup = links
+            .iter()
+            .any(|l| l.from == link.from && l.to == link.to && l.relation == link.relation);
+        if link.from != link.to && !dup {
+            links.push(link);
+        }
+    }
+    state.links = links;
+
+    (moved, merged, touched)
+}
+
+/// Move every note from one namespace into another. Notes that already exist
+/// in the target are merged like `record_note` (detail/context/tags), keeping
+/// the older creation time. Returns (moved, merged).
+pub fn merge_namespace(from: &str, into: &str) -> Result<(usize, usize), String> {
+    if from == into {
+        return Err(format!("cannot merge namespace '{from}' into itself"));
+    }
+    let mut state = load();
+    let (moved, merged, touched) = merge_notes(&mut state, from, into);
+    if touched.is_empty() {
+        return Err(format!("namespace '{from}' has no notes"));
+    }
+
+    save(&state);
+
+    if let Some(client) = crate::atomic::AtomicClient::from_env() {
+        for (old_hash, new_hash) in &touched {
+            dual_write(&client, new_hash, &state.notes[new_hash]);
+            if let Err(e) = client.destroy(&client.note_url(old_hash)) {
+                log::warn!("atomic-server: {e} (queued)");
+                queue_outbox("destroy", old_hash);
+            }
+        }
+    }
+
+    Ok((moved, merged))
+}
+
+// --- Timeline ---
+
+/// Parse a `--since`/`--until` bound into an RFC 3339 prefix comparable with
+/// note timestamps: relative ("30m", "12h", "7d", "2w"), a date ("2026-02-01")
+/// or a full UTC timestamp ("2026-02-01T10:00:00Z").
+pub fn parse_time_bound(spec: &str) -> Result<String, String> {
+    let spec = spec.trim();
+    if spec.get(4..5) == Some("-") {
+        // YYYY-MM-DD or full YYYY-MM-DDTHH:MM:SSZ; a real date round-trips.
+        let full = if spec.len() == 10 {
+            format!("{spec}T00:00:00Z")
+        } else {
+            spec.to_string()
+        };
+        return match parse_rfc3339(&full).map(rfc3339) {
+            Some(canonical) if canonical == full => Ok(spec.to_string()),
+            _ => Err(format!(
+                "bad date: '{spec}' (use YYYY-MM-DD or YYYY-MM-DDTHH:MM:SSZ)"
+            )),
+        };
+    }
+    let bad = || format!("bad time: '{spec}' (use 7d, 12h, 2w or YYYY-MM-DD)");
+    let (num, unit_secs) = [("m", 60), ("h", 3600), ("d", 86400), ("w", 7 * 86400)]
+        .into_iter()
+        .find_map(|(unit, secs)| spec.strip_suffix(unit).map(|num| (num, secs)))
+        .ok_or_else(|| format!("bad time unit in '{spec}' (m, h, d, w)"))?;
+    let n: u64 = num.parse().map_err(|_| bad())?;
+    let secs = n.checked_mul(unit_secs).ok_or_else(bad)?;
+    Ok(rfc3339(now_secs().saturating_sub(secs)))
+}
+
+/// Notes bucketed by day (newest first) since an RFC 3339 bound.
+/// Each bucket counts new notes per kind and notes resolved/superseded that
+/// day, with highlights for new issues/decisions and status changes.
+pub fn timeline(since: &str) -> Vec<serde_json::Value> {
+    use std::collections::BTreeMap;
+
+    #[derive(Default)]
+    struct Day {
+        created: usize,
+        by_kind: BTreeMap<String, usize>,
+        closed: usize,
+        highlights: Vec<serde_json::Value>,
+    }
+
+    let state = shared();
+    let mut days: BTreeMap<String, Day> = BTreeMap::new();
+
+    for (hash, note) in &state.notes {
+        if !in_namespace(note) {
+            continue;
+        }
+        let short = &hash[..12.min(hash.len())];
+        if note.created.as_str() >= since {
+            let day = days
+                .entry(note.created[..10.min(note.created.len())].to_string())
+                .or_default();
+            day.created += 1;
+            *day.by_kind.entry(note.kind.clone()).or_default() += 1;
+            if note.kind == "issue" || note.kind == "decision" {
+                day.highlights.push(serde_json::json!({
+                    "event": format!("new_{}", note.kind),
+                    "hash": short,
+                    "summary": note.summary,
+                    "time": note.created,
+                }));
+            }
+        }
+        let closed = note.status == "resolved" || note.status == "superseded";
+        if closed && !note.updated.is_empty() && note.updated.as_str() >= since {
+            let day = days
+                .entry(note.updated[..10.min(note.updated.len())].to_string())
+                .or_default();
+            day.closed += 1;
+            day.highlights.push(serde_json::json!({
+                "event": note.status,
+                "hash": short,
+                "kind": note.kind,
+                "summary": note.summary,
+                "time": note.updated,
+            }));
+        }
+    }
+
+    days.into_iter()
+        .rev()
+        .map(|(date, mut day)| {
+            day.highlights.sort_by(|a, b| {
+                let ta = a["time"].as_str().unwrap_or("");
+                let tb = b["time"].as_str().unwrap_or("");
+                tb.cmp(ta)
+            });
+            serde_json::json!({
+                "day": date,
+                "notes": day.created,
+                "by_kind": day.by_kind,
+                "closed": day.closed,
+                "highlights": day.highlights,
+            })
+        })
+        .collect()
+}
+
+// --- Graph export ---
+
+/// Render the active namespace's notes and links as Graphviz DOT or GraphML.
+/// Notes are filtered by tag/context; links are kept when both ends survive.
+pub fn export_graph(
+    format: &str,
+    tag: Option<&str>,
+    context: Option<&str>,
+) -> Result<String, String> {
+    let state = shared();
+    let mut notes: Vec<(&String, &MemoryNote)> = state
+        .notes
+        .iter()
+        .filter(|(_, n)| {
+            in_namespace(n)
+                && (tag.is_none() || n.tags.iter().any(|t| Some(t.as_str()) == tag))
+                && (context.is_none() || context == Some(n.context.as_str()))
+        })
+        .collect();
+    notes.sort_by(|a, b| a.1.created.cmp(&b.1.created).then_with(|| a.0.cmp(b.0)));
+    render_graph(format, &notes, &state.links)
+}
+
+/// Render notes (already filtered and ordered) and the links between them.
+fn render_graph(
+    format: &str,
+    notes: &[(&String, &MemoryNote)],
+    links: &[NoteLink],
+) -> Result<String, String> {
+    let ids: std::collections::HashSet<&str> = notes.iter().map(|(h, _)| h.as_str()).collect();
+    let links: Vec<&NoteLink> = links
+        .iter()
+        .filter(|l| ids.contains(l.from.as_str()) && ids.contains(l.to.as_str()))
+        .collect();
+    let short = |h: &str| h[..12.min(h.len())].to_string();
+
+    let mut out = String::new();
+    match format {
+        "dot" => {
+            let esc = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+            out.push_str("digraph esc_memory {\n");
+            out.push_str("  node [shape=box];\n");
+            for (hash, note) in notes {
+                out.push_str(&format!(
+                    "  \"{}\" [label=\"[{}] {}\", kind=\"{}\", context=\"{}\", tags=\"{}\", status=\"{}\"];\n",
+                    short(hash),
+                    esc(&note.kind),
+                    esc(&note.summary),
+                    esc(&note.kind),
+                    esc(&note.context),
+                    esc(&note.tags.join(",")),
+                    esc(&note.status),
+                ));
+            }
+            for link in &links {
+                out.push_str(&format!(
+                    "  \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                    short(&link.from),
+                    short(&link.to),
+                    esc(&link.relation),
+                ));
+            }
+            out.push_str("}\n");
+        }
+        "graphml" => {
+            let esc = |s: &str| {
+                s.replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;")
+                    .replace('"', "&quot;")
+            };
+            out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+            out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+            for key in ["kind", "summary", "context", "tags", "status", "created"] {
+                out.push_str(&format!(
+                    "  <key id=\"{key}\" for=\"node\" attr.name=\"{key}\" attr.type=\"string\"/>\n"
+                ));
+            }
+            out.push_str(
+                "  <key id=\"relation\" for=\"edge\" attr.name=\"relation\" attr.type=\"string\"/>\n",
+            );
+            out.push_str("  <graph id=\"esc_memory\" edgedefault=\"directed\">\n");
+            for (hash, note) in notes {
+                out.push_str(&format!("    <node id=\"{}\">\n", short(hash)));
+                for (key, value) in [
+                    ("kind", note.kind.clone()),
+                    ("summary", note.summary.clone()),
+                    ("context", note.context.clone()),
+                    ("tags", note.tags.join(",")),
+                    ("status", note.status.clone()),
+                    ("created", note.created.clone()),
+                ] {
+                    out.push_str(&format!(
+                        "      <data key=\"{key}\">{}</data>\n",
+                        esc(&value)
+                    ));
+                }
+                out.push_str("    </node>\n");
+            }
+            for link in &links {
+                out.push_str(&format!(
+                    "    <edge source=\"{}\" target=\"{}\"><data key=\"relation\">{}</data></edge>\n",
+                    short(&link.from),
+                    short(&link.to),
+                    esc(&link.relation),
+                ));
+            }
+            out.push_str("  </graph>\n</graphml>\n");
+        }
+        other => return Err(format!("unknown graph format: {other} (dot, graphml)")),
+    }
+    Ok(out)
+}
+
+// --- Outbox: failed dual-writes, replayed on sync ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OutboxEntry {
+    /// upsert or destroy
+    op: String,
+    hash: String,
+    queued: String,
+}
+
+/// Upsert a note on atomic-server; on failure warn and queue it for `sync`.
+fn dual_write(client: &crate::atomic::AtomicClient, hash: &str, note: &MemoryNote) {
+    if let Err(e) = atomic_record_note(client, hash, note) {
+        log::warn!("atomic-server: {e} (queued)");
+        queue_outbox("upsert", hash);
+    }
+}
+
+fn queue_outbox(op: &str, hash: &str) {
+    use std::io::Write;
+    log::debug!("outbox: queue {op} {}", &hash[..12.min(hash.len())]);
+    let entry = OutboxEntry {
+        op: op.to_string(),
+        hash: hash.to_string(),
+        queued: now_rfc3339(),
+    };
+    let path = outbox_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(mut f) = fs::OpenOptions::new().create(true).append(true).open(&path) {
+        let _ = writeln!(f, "{}", serde_json::to_string(&entry).unwrap_or_default());
+    }
+}
+
+fn read_outbox() -> Vec<OutboxEntry> {
+    fs::read_to_string(outbox_path())
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect()
+}
+
+/// Replay queued writes (latest op per hash wins; upserts send the current
+/// local note). Entries that still fail stay queued. Returns (flushed, pending).
+fn flush_outbox(client: &crate::atomic::AtomicClient, state: &MemoryState) -> (usize, usize) {
+    let mut latest: Vec<OutboxEntry> = Vec::new();
+    for entry in read_outbox() {
+        latest.retain(|e| e.hash != entry.hash);
+        latest.push(entry);
+    }
+    if latest.is_empty() {
+        return (0, 0);
+    }
+
+    let mut flushed = 0;
+    let mut pending: Vec<OutboxEntry> = Vec::new();
+    for entry in latest {
+        let result = match entry.op.as_str() {
+            "upsert" => match state.notes.get(&entry.hash) {
+                Some(note) => atomic_record_note(client, &entry.hash, note),
+                None => Ok(()), // deleted locally since; nothing to push
+            },
+            "destroy" => match client.destroy(&client.note_url(&entry.hash)) {
+                Err(e) if e.starts_with("HTTP 404") => Ok(()),
+                other => other,
+            },
+            _ => Ok(()),
+        };
+        match result {
+            Ok(()) => flushed += 1,
+            Err(e) => {
+                log::warn!(
+                    "queued {} {} — {}",
+                    entry.op,
+                    &entry.hash[..12.min(entry.hash.len())],
+                    e
+                );
+                pending.push(entry);
+            }
+        }
+    }
+
+    let path = outbox_path();
+    if pending.is_empty() {
+        let _ = fs::remove_file(&path);
+    } else {
+        let lines: Vec<String> = pending
+            .iter()
+            .map(|e| serde_json::to_string(e).unwrap_or_default())
+            .collect();
+        let tmp = path.with_extension("tmp");
+        if fs::write(&tmp, lines.join("\n") + "\n").is_ok() {
+            let _ = fs::rename(&tmp, &path);
+        }
+    }
+    (flushed, pending.len())
+}
+
 // --- Sync: bulk write all notes to atomic-server ---
 
-/// Sync all notes from flat file to atomic-server. Returns count synced.
-pub fn sync_to_atomic() -> Result<usize, String> {
-    let client = crate::atomic::AtomicClient::from_env()
-        .ok_or_else(|| "set ESC_ATOMIC_URL and ESC_ATOMIC_KEY".to_string())?;
-    let state = load();
+/// Flush the outbox, then sync all notes from flat file to atomic-server.
+/// Returns (synced, flushed, still_queued).
+pub fn sync_to_atomic() -> Result<(usize, usize, usize), String> {
+    let client = crate::atomic::AtomicClient::from_env().ok_or_else(|| {
+        "set ESC_ATOMIC_URL and ESC_ATOMIC_KEY (or atomic_url/atomic_key in a config profile)"
+            .to_string()
+    })?;
+    let state = shared();
+    let (flushed, pending) = flush_outbox(&client, &state);
     let mut count = 0;
     for (hash, note) in &state.notes {
         if let Err(e) = atomic_record_note(&client, hash, note) {
-            eprintln!("  warning: {} — {}", &hash[..12.min(hash.len())], e);
+            log::warn!("{} — {}", &hash[..12.min(hash.len())], e);
         } else {
             count += 1;
         }
     }
-    Ok(count)
+    Ok((count, flushed, pending))
+}
+
+// --- Reconcile: two-way merge with atomic-server ---
+
+/// Last modification time of a note (RFC 3339, comparable as strings).
+fn note_mtime(note: &MemoryNote) -> &str {
+    if note.updated.is_empty() {
+        &note.created
+    } else {
+        &note.updated
+    }
+}
+
+fn same_content(a: &MemoryNote, b: &MemoryNote) -> bool {
+    a.detail == b.detail && a.context == b.context && a.tags == b.tags && a.status == b.status
+}
+
+/// What reconcile does with one hash, given each side's copy.
+#[derive(Debug, PartialEq)]
+enum ReconcileAction {
+    Pull,
+    Push,
+    UpdateLocal,
+    UpdateRemote,
+    Conflict,
+    Unchanged,
+    /// Remote-only, but its destroy is still queued in the outbox
+    Skip,
+}
+
+fn reconcile_action(
+    local: Option<&MemoryNote>,
+    remote: Option<&MemoryNote>,
+    destroy_queued: bool,
+) -> ReconcileAction {
+    match (local, remote) {
+        (None, Some(_)) if destroy_queued => ReconcileAction::Skip,
+        (None, Some(_)) => ReconcileAction::Pull,
+        (Some(_), None) => ReconcileAction::Push,
+        (None, None) => ReconcileAction::Unchanged,
+        (Some(l), Some(r)) if same_content(l, r) => ReconcileAction::Unchanged,
+        (Some(l), Some(r)) => match note_mtime(l).cmp(note_mtime(r)) {
+            std::cmp::Ordering::Less => ReconcileAction::UpdateLocal,
+            std::cmp::Ordering::Greater => ReconcileAction::UpdateRemote,
+            std::cmp::Ordering::Equal => ReconcileAction::Conflict,
+        },
+    }
+}
+
+/// Max notes one `/search` call asks for during reconcile.
+const RECONCILE_SEARCH_LIMIT: usize = 1000;
+
+/// Pull every esc note from atomic-server and merge with the flat file by
+/// hash: one-sided notes are copied across, and for notes on both sides the
+/// newer modification time wins. Equal times with different content are
+/// reported as conflicts and left alone. Covers all namespaces. If the
+/// search hits its cap the report sets `truncated`: remote-only notes past
+/// the cap are not pulled. Queued writes are replayed first; notes whose
+/// destroy is still queued are reported as `skipped`, not pulled back.
+pub fn reconcile_atomic(dry_run: bool) -> Result<serde_json::Value, String> {
+    let client = crate::atomic::AtomicClient::from_env().ok_or_else(|| {
+        "set ESC_ATOMIC_URL and ESC_ATOMIC_KEY (or atomic_url/atomic_key in a config profile)"
+            .to_string()
+    })?;
+    let mut state = load();
+    if !dry_run {
+        flush_outbox(&client, &state);
+    }
+    let mut queued_destroys = std::collections::HashSet::new();
+    for entry in read_outbox() {
+        if entry.op == "destroy" {
+            queued_destroys.insert(entry.hash);
+        } else {
+            queued_destroys.remove(&entry.hash);
+        }
+    }
+
+    let (found, truncated) = client.search_capped("esc-note", RECONCILE_SEARCH_LIMIT)?;
+    let mut remote: HashMap<String, MemoryNote> = found
+        .iter()
+        .filter_map(|r| atomic_resource_to_note(&client, r))
+        .collect();
+    if truncated {
+        // Search hit its cap: local notes missing from it may still exist
+        // remotely, so look them up one by one. Remote-only notes past the
+        // cap can't be found and are not pulled.
+        log::warn!(
+            "atomic-server search capped at {RECONCILE_SEARCH_LIMIT} notes; remote-only notes beyond it are not pulled"
+        );
+        let missing: Vec<String> = state
+            .notes
+            .keys()
+            .filter(|h| !remote.contains_key(*h))
+            .cloned()
+            .collect();
+        for hash in missing {
+            if let Ok(r) = client.get(&client.note_url(&hash))
+                && let Some((h, note)) = atomic_resource_to_note(&client, &r)
+            {
+                remote.insert(h, note);
+            }
+        }
+    }
+
+    let short = |h: &str| h[..12.min(h.len())].to_string();
+    let mut pulled = Vec::new();
+    let mut pushed = Vec::new();
+    let mut updated_local = Vec::new();
+    let mut updated_remote = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut errors = Vec::new();
+    let mut unchanged = 0;
+    let mut skipped = Vec::new();
+    let mut to_push: Vec<String> = Vec::new();
+
+    let hashes: Vec<String> = remote
+        .keys()
+        .chain(state.notes.keys().filter(|h| !remote.contains_key(*h)))
+        .cloned()
+        .collect();
+    for hash in hashes {
+        let lnote = state.notes.get(&hash);
+        let rnote = remote.get(&hash);
+        match reconcile_action(lnote, rnote, queued_destroys.contains(&hash)) {
+            ReconcileAction::Pull | ReconcileAction::UpdateLocal => {
+                if lnote.is_none() {
+                    pulled.push(short(&hash));
+                } else {
+                    updated_local.push(short(&hash));
+                }
+                if !dry_run {
+                    state.notes.insert(hash.clone(), remote[&hash].clone());
+                }
+            }
+            ReconcileAction::Push => {
+                pushed.push(short(&hash));
+                to_push.push(hash);
+            }
+            ReconcileAction::UpdateRemote => {
+                updated_remote.push(short(&hash));
+                to_push.push(hash);
+            }
+            ReconcileAction::Conflict => {
+                let (l, r) = (&state.notes[&hash], &remote[&hash]);
+                conflicts.push(serde_json::json!({
+                    "hash": short(&hash),
+                    "summary": l.summary,
+                    "modified": note_mtime(l),
+                    "local": {"status": l.status, "context": l.context, "tags": l.tags, "detail": l.detail},
+                    "remote": {"status": r.status, "context": r.context, "tags": r.tags, "detail": r.detail},
+                }));
+            }
+            ReconcileAction::Unchanged => unchanged += 1,
+            ReconcileAction::Skip => skipped.push(short(&hash)),
+        }
+    }
+
+    if !dry_run {
+        if !pulled.is_empty() || !updated_local.is_empty() {
+            save(&state);
+        }
+        for hash in &to_push {
+            if let Err(e) = atomic_record_note(&client, hash, &state.notes[hash]) {
+                errors.push(serde_json::json!({"hash": short(hash), "error": e}));
+            }
+        }
+    }
+
+    Ok(serde_json::json!({
+        "dry_run": dry_run,
+        "pulled": pulled,
+        "pushed": pushed,
+        "updated_local": updated_local,
+        "updated_remote": updated_remote,
+        "conflicts": conflicts,
+        "errors": errors,
+        "unchanged": unchanged,
+        "skipped": skipped,
+        "truncated": truncated,
+    }))
 }
 
 /// Delete notes from atomic-server that don't exist in local memory.
 /// Uses search to discover remote notes, then destroys any not in local.
 /// Returns (deleted_count, error_count).
 pub fn purge_atomic() -> Result<(usize, usize), String> {
-    let client = crate::atomic::AtomicClient::from_env()
-        .ok_or_else(|| "set ESC_ATOMIC_URL and ESC_ATOMIC_KEY".to_string())?;
-    let state = load();
+    let client = crate::atomic::AtomicClient::from_env().ok_or_else(|| {
+        "set ESC_ATOMIC_URL and ESC_ATOMIC_KEY (or atomic_url/atomic_key in a config profile)"
+            .to_string()
+    })?;
+    let state = shared();
 
     // Search atomic-server for all esc-note resources
     // Use "esc-note" as query since all notes have that in their name field
@@ -422,21 +1475,21 @@ pub fn purge_atomic() -> Result<(usize, usize), String> {
         if !exists_locally {
             // Get summary for logging
             let summary = r
-                .get(&client.prop_url("note-summary"))
+                .get(client.prop_url("note-summary"))
                 .and_then(|v| v.as_str())
                 .unwrap_or("(no summary)");
             let context = r
-                .get(&client.prop_url("note-context"))
+                .get(client.prop_url("note-context"))
                 .and_then(|v| v.as_str())
                 .unwrap_or("?");
 
             match client.destroy(&subject) {
                 Ok(()) => {
-                    eprintln!("  destroyed [{context}] {remote_hash}: {summary}");
+                    log::info!("  destroyed [{context}] {remote_hash}: {summary}");
                     deleted += 1;
                 }
                 Err(e) => {
-                    eprintln!("  error {remote_hash}: {e}");
+                    log::error!("{remote_hash}: {e}");
                     errors += 1;
                 }
             }
@@ -448,21 +1501,26 @@ pub fn purge_atomic() -> Result<(usize, usize), String> {
 
 // --- Atomic-server backend ---
 
-/// Recall via atomic-server: full-text search for notes.
+/// Recall via atomic-server: full-text search for notes. The flag is
+/// `search_capped`'s: more hits may exist past `limit`.
 fn atomic_recall(
     client: &crate::atomic::AtomicClient,
     query: &str,
+    filter: &NoteFilter,
     limit: usize,
-) -> Result<Vec<serde_json::Value>, String> {
+) -> Result<(Vec<serde_json::Value>, bool), String> {
     use std::collections::HashSet;
 
     let mut results: Vec<serde_json::Value> = Vec::new();
     let mut seen: HashSet<String> = HashSet::new();
 
-    let notes = client.search(query, limit)?;
+    let (notes, capped) = client.search_capped(query, limit)?;
 
     for r in notes {
         if let Some((hash, note)) = atomic_resource_to_note(client, &r) {
+            if !in_namespace(&note) || !filter.matches(&note) {
+                continue;
+            }
             let short = hash[..12.min(hash.len())].to_string();
             if seen.insert(format!("n:{short}")) {
                 results.push(compact_note(&short, &note, "direct"));
@@ -471,7 +1529,24 @@ fn atomic_recall(
     }
 
     results.truncate(limit);
-    Ok(results)
+    Ok((results, capped))
+}
+
+/// Whether the server defines the `updated` property, which was added after
+/// the note schema. Probed once per process; servers whose schema predates
+/// it reject commits that set it, so it's left out until `memory setup` runs.
+/// Errors other than a 404 count as present and surface on the write itself.
+fn server_has_updated_prop(client: &crate::atomic::AtomicClient) -> bool {
+    static HAS_UPDATED: OnceLock<bool> = OnceLock::new();
+    *HAS_UPDATED.get_or_init(|| match client.get(&client.prop_url("updated")) {
+        Err(e) if e.starts_with("HTTP 404") => {
+            log::warn!(
+                "atomic-server schema predates note modification times; run `esc memory setup` to sync them"
+            );
+            false
+        }
+        _ => true,
+    })
 }
 
 /// Write a MemoryNote to atomic-server.
@@ -489,7 +1564,7 @@ pub fn atomic_record_note(
         note.tags.join(" ")
     );
     let search_desc = format!("{} | {}", note.summary, note.detail);
-    let set = serde_json::json!({
+    let mut set = serde_json::json!({
         "https://atomicdata.dev/properties/name": search_name.trim(),
         "https://atomicdata.dev/properties/description": search_desc,
         client.prop_url("note-kind"): note.kind,
@@ -502,6 +1577,14 @@ pub fn atomic_record_note(
         "https://atomicdata.dev/properties/isA": [client.class_url("note")],
         "https://atomicdata.dev/properties/parent": client.server_url,
     });
+    if !note.updated.is_empty() && server_has_updated_prop(client) {
+        set[client.prop_url("updated")] = serde_json::json!(note.updated);
+    }
+    // Only written outside the default namespace, so servers set up before
+    // namespaces existed keep accepting default-namespace commits.
+    if note.namespace != DEFAULT_NAMESPACE {
+        set[client.prop_url("namespace")] = serde_json::json!(note.namespace);
+    }
     client.upsert(&subject, &set)
 }
 
@@ -511,12 +1594,12 @@ fn atomic_resource_to_note(
 ) -> Option<(String, MemoryNote)> {
     let p = |name| client.prop_url(name);
     let kind = r
-        .get(&p("note-kind"))
+        .get(p("note-kind"))
         .and_then(|v| v.as_str())
         .unwrap_or("")
         .to_string();
     let summary = r
-        .get(&p("note-summary"))
+        .get(p("note-summary"))
         .and_then(|v| v.as_str())
         .unwrap_or("")
         .to_string();
@@ -524,33 +1607,43 @@ fn atomic_resource_to_note(
         return None;
     }
     let detail = r
-        .get(&p("note-detail"))
+        .get(p("note-detail"))
         .and_then(|v| v.as_str())
         .unwrap_or("")
         .to_string();
     let context = r
-        .get(&p("note-context"))
+        .get(p("note-context"))
         .and_then(|v| v.as_str())
         .unwrap_or("")
         .to_string();
-    let tags_str = r.get(&p("tags")).and_then(|v| v.as_str()).unwrap_or("");
+    let tags_str = r.get(p("tags")).and_then(|v| v.as_str()).unwrap_or("");
     let tags: Vec<String> = if tags_str.is_empty() {
         vec![]
     } else {
         tags_str.split(',').map(|s| s.trim().to_string()).collect()
     };
     let created = r
-        .get(&p("created"))
+        .get(p("created"))
         .and_then(|v| v.as_str())
         .unwrap_or("")
         .to_string();
     let status = r
-        .get(&p("status"))
+        .get(p("status"))
         .and_then(|v| v.as_str())
         .unwrap_or("active")
         .to_string();
+    let updated = r
+        .get(p("updated"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+    let namespace = r
+        .get(p("namespace"))
+        .and_then(|v| v.as_str())
+        .unwrap_or(DEFAULT_NAMESPACE)
+        .to_string();
 
-    let full_hash = note_hash(&kind, &summary);
+    let full_hash = note_hash(&namespace, &kind, &summary);
     Some((
         full_hash,
         MemoryNote {
@@ -561,18 +1654,26 @@ fn atomic_resource_to_note(
             tags,
             created,
             status,
+            updated,
+            namespace,
         },
     ))
 }
 
 // --- Time helpers ---
 
-fn now_rfc3339() -> String {
-    let duration = std::time::SystemTime::now()
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default();
-    let secs = duration.as_secs();
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn now_rfc3339() -> String {
+    rfc3339(now_secs())
+}
 
+fn rfc3339(secs: u64) -> String {
     let days = secs / 86400;
     let time_secs = secs % 86400;
     let hours = time_secs / 3600;
@@ -587,6 +1688,28 @@ fn now_rfc3339() -> String {
     )
 }
 
+/// Parse the `YYYY-MM-DDTHH:MM:SSZ` timestamps written by `rfc3339`.
+fn parse_rfc3339(s: &str) -> Option<u64> {
+    let num = |r: std::ops::Range<usize>| s.get(r)?.parse::<u64>().ok();
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    let (hours, minutes, seconds) = (num(11..13)?, num(14..16)?, num(17..19)?);
+    if !(1..=12).contains(&month) || year < 1970 {
+        return None;
+    }
+    let mut days = 0;
+    for y in 1970..year {
+        days += if is_leap(y) { 366 } else { 365 };
+    }
+    let month_days = if is_leap(year) {
+        [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
+    } else {
+        [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
+    };
+    days += month_days[..(month - 1) as usize].iter().sum::<u64>();
+    days += day.checked_sub(1)?;
+    Some(days * 86400 + hours * 3600 + minutes * 60 + seconds)
+}
+
 fn days_to_ymd(mut days: u64) -> (u64, u64, u64) {
     let mut year = 1970;
     loop {
@@ -617,5 +1740,241 @@ fn days_to_ymd(mut days: u64) -> (u64, u64, u64) {
 }
 
 fn is_leap(year: u64) -> bool {
-    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        HashLookupError, MemoryNote, MemoryState, NoteLink, Page, ReconcileAction, merge_notes,
+        note_hash, parse_time_bound, reconcile_action, render_graph, resolve_hash,
+    };
+
+    fn note(summary: &str, namespace: &str) -> MemoryNote {
+        serde_json::from_value(serde_json::json!({
+            "kind": "discovery",
+            "summary": summary,
+            "created": "2026-01-01T00:00:00Z",
+            "namespace": namespace,
+        }))
+        .unwrap()
+    }
+
+    fn state(notes: &[(&str, MemoryNote)]) -> MemoryState {
+        MemoryState {
+            version: 1,
+            notes: notes
+                .iter()
+                .map(|(h, n)| (h.to_string(), n.clone()))
+                .collect(),
+            links: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn resolve_hash_needs_exactly_one_match() {
+        let s = state(&[
+            ("abc111", note("a", "default")),
+            ("abc222", note("b", "default")),
+            ("abc", note("c", "default")),
+        ]);
+        assert_eq!(resolve_hash(&s, "abc1"), Ok("abc111".to_string()));
+        assert_eq!(resolve_hash(&s, "abc"), Ok("abc".to_string()));
+        assert_eq!(resolve_hash(&s, ""), Err(HashLookupError::Empty));
+        assert_eq!(
+            resolve_hash(&s, "ab"),
+            Err(HashLookupError::Ambiguous("ab".to_string(), 3))
+        );
+        assert_eq!(
+            resolve_hash(&s, "zz"),
+            Err(HashLookupError::NotFound("zz".to_string()))
+        );
+    }
+
+    fn page(limit: usize, offset: usize, cursor: Option<&str>) -> Page {
+        Page {
+            limit,
+            offset,
+            cursor: cursor.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn full_page_does_not_pull_further_items() {
+        // The tail stands in for the lazy atomic-server query.
+        let items = ["a", "b", "c"]
+            .into_iter()
+            .chain(std::iter::once_with(|| -> &str {
+                panic!("pulled past the page")
+            }));
+        let got = page(2, 1, None).collect(items, |s| s).unwrap();
+        assert_eq!(got, ["b", "c"]);
+    }
+
+    #[test]
+    fn cursor_resumes_after_match_or_errors() {
+        let items = ["aa1", "bb2", "cc3"];
+        let got = page(5, 0, Some("bb")).collect(items, |s| s).unwrap();
+        assert_eq!(got, ["cc3"]);
+        assert!(page(5, 0, Some("zz")).collect(items, |s| s).is_err());
+    }
+
+    #[test]
+    fn cursor_inside_lazy_tail_resumes_there() {
+        let items = ["aa1", "bb2"]
+            .into_iter()
+            .chain(std::iter::once_with(|| vec!["cc3", "dd4", "ee5"]).flatten());
+        let got = page(1, 0, Some("dd")).collect(items, |s| s).unwrap();
+        assert_eq!(got, ["ee5"]);
+    }
+
+    #[test]
+    fn covered_counts_from_the_cursor() {
+        let hashes = ["aa1", "bb2", "cc3", "dd4"];
+        assert!(page(2, 0, None).covered(&hashes));
+        assert!(page(2, 2, None).covered(&hashes));
+        assert!(!page(2, 3, None).covered(&hashes));
+        assert!(page(2, 0, Some("bb")).covered(&hashes));
+        assert!(!page(2, 0, Some("cc")).covered(&hashes));
+        assert!(!page(1, 0, Some("zz")).covered(&hashes));
+    }
+
+    fn link(from: &str, to: &str) -> NoteLink {
+        NoteLink {
+            from: from.to_string(),
+            to: to.to_string(),
+            relation: "about".into(),
+            created: "2026-01-01T00:00:00Z".into(),
+        }
+    }
+
+    #[test]
+    fn merge_folds_collisions_and_repoints_links() {
+        let hash = |ns: &str, summary: &str| note_hash(ns, "discovery", summary);
+        let (work_shared, work_solo) = (hash("work", "shared"), hash("work", "solo"));
+        let (shared, solo, other) = (
+            hash("default", "shared"),
+            hash("default", "solo"),
+            hash("default", "other"),
+        );
+
+        let mut incoming = note("shared", "work");
+        incoming.detail = "from work".into();
+        incoming.tags = vec!["x".into()];
+        incoming.created = "2025-12-01T00:00:00Z".into();
+        let mut existing = note("shared", "default");
+        existing.tags = vec!["y".into()];
+
+        let mut s = state(&[
+            (&work_shared, incoming),
+            (&work_solo, note("solo", "work")),
+            (&shared, existing),
+            (&other, note("other", "default")),
+        ]);
+        s.links = vec![
+            link(&work_solo, &other),
+            link(&other, &work_shared),
+            link(&other, &shared),
+            link(&work_shared, &shared),
+        ];
+
+        let (moved, merged, touched) = merge_notes(&mut s, "work", "default");
+        assert_eq!((moved, merged, touched.len()), (1, 1, 2));
+        assert!(s.notes.values().all(|n| n.namespace == "default"));
+        assert!(s.notes.contains_key(&solo));
+
+        let kept = &s.notes[&shared];
+        assert_eq!(kept.detail, "from work");
+        assert_eq!(kept.tags, ["y", "x"]);
+        assert_eq!(kept.created, "2025-12-01T00:00:00Z");
+
+        // The old-namespace link is repointed; the duplicate it creates and
+        // the one that collapses onto the merged note are dropped.
+        let edges: Vec<(&str, &str)> = s
+            .links
+            .iter()
+            .map(|l| (l.from.as_str(), l.to.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            [
+                (solo.as_str(), other.as_str()),
+                (other.as_str(), shared.as_str())
+            ]
+        );
+    }
+
+    #[test]
+    fn graph_escapes_labels_per_format() {
+        let (a, b) = ("aaaaaaaaaaaa01".to_string(), "bbbbbbbbbbbb02".to_string());
+        let (quoted, markup) = (
+            note(r#"say "hi" \ there"#, "default"),
+            note(r#"a & <b> "c""#, "default"),
+        );
+        let notes = [(&a, &quoted), (&b, &markup)];
+        let links = [link(&a, &b)];
+
+        let dot = render_graph("dot", &notes, &links).unwrap();
+        assert!(dot.contains(r#"label="[discovery] say \"hi\" \\ there""#));
+        assert!(dot.contains(r#""aaaaaaaaaaaa" -> "bbbbbbbbbbbb" [label="about"];"#));
+
+        let xml = render_graph("graphml", &notes, &links).unwrap();
+        assert!(xml.contains(r#"<data key="summary">a &amp; &lt;b&gt; &quot;c&quot;</data>"#));
+        assert!(xml.contains(r#"<edge source="aaaaaaaaaaaa" target="bbbbbbbbbbbb">"#));
+
+        assert!(render_graph("svg", &notes, &links).is_err());
+    }
+
+    #[test]
+    fn reconcile_picks_side_by_presence_then_mtime() {
+        use ReconcileAction::*;
+        let old = note("n", "default");
+        let mut edited = old.clone();
+        edited.detail = "more".into();
+        edited.updated = "2026-01-02T00:00:00Z".into();
+        let mut tied = old.clone();
+        tied.detail = "other".into();
+
+        assert_eq!(reconcile_action(None, Some(&old), false), Pull);
+        assert_eq!(reconcile_action(None, Some(&old), true), Skip);
+        assert_eq!(reconcile_action(Some(&old), None, false), Push);
+        assert_eq!(reconcile_action(Some(&old), Some(&old), false), Unchanged);
+        assert_eq!(
+            reconcile_action(Some(&old), Some(&edited), false),
+            UpdateLocal
+        );
+        assert_eq!(
+            reconcile_action(Some(&edited), Some(&old), false),
+            UpdateRemote
+        );
+        assert_eq!(reconcile_action(Some(&old), Some(&tied), false), Conflict);
+    }
+
+    #[test]
+    fn time_bound_rejects_bad_input_without_panicking() {
+        assert!(parse_time_bound("7д").is_err());
+        assert!(parse_time_bound("é").is_err());
+        assert!(parse_time_bound("").is_err());
+        assert!(parse_time_bound("7").is_err());
+        assert!(parse_time_bound("d").is_err());
+        assert!(parse_time_bound("abcd-ef-ghij").is_err());
+        assert!(parse_time_bound("2026-13-45garbage").is_err());
+        assert!(parse_time_bound("2026-13-01").is_err());
+        assert!(parse_time_bound("2026-02-30").is_err());
+        assert!(parse_time_bound("2026-02-01T25:00:00Z").is_err());
+        assert!(parse_time_bound("2026-02-01junk").is_err());
+        assert!(parse_time_bound("99999999999999999w").is_err());
+    }
+
+    #[test]
+    fn time_bound_accepts_dates_and_relative() {
+        assert_eq!(parse_time_bound("2026-02-01").unwrap(), "2026-02-01");
+        assert_eq!(
+            parse_time_bound("2026-02-01T10:00:00Z").unwrap(),
+            "2026-02-01T10:00:00Z"
+        );
+        let week_ago = parse_time_bound("1w").unwrap();
+        assert!(week_ago < parse_time_bound("1d").unwrap());
+        assert!(week_ago.ends_with('Z'));
+    }
 }